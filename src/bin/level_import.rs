@@ -0,0 +1,42 @@
+use ggez::{GameError, GameResult};
+use sonic_platformer::level::tiled;
+use std::env;
+use std::fs::{self, File};
+use std::path::Path;
+
+const USAGE: &str = "Usage: level_import <map.tmx|map.tmj> <level.json> [resources directory]";
+
+/// Imports a Tiled map and saves it as a level file.
+///
+/// The tileset image path stored on the level is made relative to
+/// the resources directory, which defaults to `resources`.
+fn main() -> GameResult {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if (args.len() < 2) || (args.len() > 3) {
+        eprintln!("{}", USAGE);
+        std::process::exit(1);
+    }
+    let (input, output) = (&args[0], &args[1]);
+    let resources = Path::new(args.get(2).map(String::as_str).unwrap_or("resources"));
+
+    let mut level = tiled::import(input, |path| {
+        fs::read_to_string(path)
+            .map_err(|e| GameError::ResourceLoadError(format!("Could not read {}: {}", path, e)))
+    })?;
+
+    if let Ok(relative) = Path::new(&level.tileset).strip_prefix(resources) {
+        level.tileset = format!("/{}", relative.to_string_lossy().replace('\\', "/"));
+    }
+
+    level.write(File::create(output)?)?;
+    println!(
+        "Imported {} into {}: {}x{} chunks, {} unique chunks, {} objects",
+        input,
+        output,
+        level.foreground.width,
+        level.foreground.height,
+        level.chunks.len(),
+        level.objects.len()
+    );
+    Ok(())
+}
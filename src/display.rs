@@ -0,0 +1,137 @@
+use ggez::conf::NumSamples;
+use ggez::graphics::{self, BlendMode, Canvas, Color, DrawParam, Drawable, FilterMode, Rect};
+use ggez::{Context, GameResult};
+use glam::*;
+
+/// Describes how the virtual screen is scaled to fit the window.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Scales by the largest integer factor which fits the window,
+    /// so that every virtual pixel has the same size.
+    #[default]
+    Integer,
+    /// Scales by the largest factor which fits the window, keeping
+    /// the aspect ratio.
+    Aspect,
+    /// Stretches the virtual screen over the whole window.
+    Stretch,
+}
+
+/// Renders the game into an offscreen canvas with a fixed virtual
+/// resolution, which is then scaled to the window with no filtering.
+///
+/// Once set up, screen coordinates are always given in virtual
+/// pixels, so that cameras and HUDs work the same way regardless of
+/// the window size.
+pub struct Display {
+    width: u16,
+    height: u16,
+    mode: ScaleMode,
+    canvas: Option<Canvas>,
+}
+
+impl Display {
+    /// Creates a new display with a certain virtual resolution.
+    pub fn new(width: u16, height: u16, mode: ScaleMode) -> Self {
+        Self {
+            width,
+            height,
+            mode,
+            canvas: None,
+        }
+    }
+
+    /// Sets up the offscreen canvas and switches screen coordinates
+    /// to virtual pixels. Must be called before anything that depends
+    /// on the screen size is created, such as cameras.
+    pub fn setup(&mut self, context: &mut Context) -> GameResult {
+        let format = graphics::get_window_color_format(context);
+        let mut canvas = Canvas::new(context, self.width, self.height, NumSamples::One, format)?;
+        canvas.set_filter(FilterMode::Nearest);
+        canvas.set_blend_mode(Some(BlendMode::Replace));
+        self.canvas = Some(canvas);
+        graphics::set_screen_coordinates(context, self.dimensions())
+    }
+
+    /// Gets the virtual screen area, in virtual pixels.
+    pub fn dimensions(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.width as f32, self.height as f32)
+    }
+
+    /// Gets how the virtual screen is scaled to the window.
+    pub fn mode(&self) -> ScaleMode {
+        self.mode
+    }
+
+    /// Changes how the virtual screen is scaled to the window.
+    pub fn set_mode(&mut self, mode: ScaleMode) {
+        self.mode = mode;
+    }
+
+    /// Gets the area of the window where the virtual screen is shown,
+    /// in window pixels.
+    pub fn viewport(&self, context: &Context) -> Rect {
+        let (window_width, window_height) = graphics::drawable_size(context);
+        let window = glam::vec2(window_width, window_height);
+        let size = glam::vec2(self.width as f32, self.height as f32);
+        if self.mode == ScaleMode::Stretch {
+            return Rect::new(0.0, 0.0, window.x, window.y);
+        }
+
+        let mut scale = (window.x / size.x).min(window.y / size.y);
+        if self.mode == ScaleMode::Integer {
+            scale = scale.floor().max(1.0);
+        }
+        let scaled = size * scale;
+        let corner = ((window - scaled) / 2.0).floor();
+        Rect::new(corner.x, corner.y, scaled.x, scaled.y)
+    }
+
+    /// Converts a point from window pixels to virtual pixels, such as
+    /// the mouse position.
+    pub fn to_virtual(&self, context: &Context, point: Vec2) -> Vec2 {
+        let viewport = self.viewport(context);
+        let scale = glam::vec2(
+            self.width as f32 / viewport.w,
+            self.height as f32 / viewport.h,
+        );
+        (point - glam::vec2(viewport.x, viewport.y)) * scale
+    }
+
+    /// Starts rendering a frame into the offscreen canvas, clearing it.
+    pub fn begin(&self, context: &mut Context) -> GameResult {
+        graphics::set_canvas(context, self.canvas.as_ref());
+        graphics::set_screen_coordinates(context, self.dimensions())?;
+        graphics::clear(context, Color::BLACK);
+        Ok(())
+    }
+
+    /// Finishes rendering a frame, drawing the offscreen canvas to
+    /// the window. Any area of the window not covered by the virtual
+    /// screen is left black.
+    pub fn finish(&self, context: &mut Context) -> GameResult {
+        graphics::set_canvas(context, None);
+        let canvas = match &self.canvas {
+            Some(canvas) => canvas,
+            None => return Ok(()),
+        };
+
+        let (window_width, window_height) = graphics::drawable_size(context);
+        graphics::set_screen_coordinates(
+            context,
+            Rect::new(0.0, 0.0, window_width, window_height),
+        )?;
+        graphics::clear(context, Color::BLACK);
+        let viewport = self.viewport(context);
+        canvas.draw(
+            context,
+            DrawParam::default()
+                .dest(glam::vec2(viewport.x, viewport.y))
+                .scale(glam::vec2(
+                    viewport.w / self.width as f32,
+                    viewport.h / self.height as f32,
+                )),
+        )?;
+        graphics::set_screen_coordinates(context, self.dimensions())
+    }
+}
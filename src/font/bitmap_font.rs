@@ -0,0 +1,118 @@
+use super::format::FontData;
+use super::layout::{Glyph, TextLayout, TextStyle};
+use crate::objects::atlas_format::{AtlasData, AtlasFrameData};
+use crate::objects::sprite_atlas::SpriteAtlas;
+use ggez::{Context, GameResult};
+use glam::*;
+use std::collections::HashMap;
+
+/// A font made of glyphs on an image, drawn through a sprite atlas.
+///
+/// Texts are laid out once and then queued for drawing, so that
+/// static texts such as menus are laid out a single time. Queued texts
+/// are drawn as a single batch.
+pub struct BitmapFont {
+    atlas: SpriteAtlas,
+    glyphs: HashMap<char, Glyph>,
+    fallback: Option<char>,
+    line_height: f32,
+}
+
+impl BitmapFont {
+    /// Loads a font from a JSON file on the resources directory.
+    pub fn load(context: &mut Context, path: &str) -> GameResult<Self> {
+        let data = FontData::load(context, path)?;
+        Self::from_data(context, &data)
+    }
+
+    /// Creates a font from font data.
+    pub fn from_data(context: &mut Context, data: &FontData) -> GameResult<Self> {
+        let mut atlas = AtlasData {
+            image: data.image.clone(),
+            frames: Vec::new(),
+            animations: Vec::new(),
+        };
+        let mut glyphs = HashMap::new();
+        for glyph in data.glyphs.iter() {
+            let drawn = (glyph.rect[2] > 0.0) && (glyph.rect[3] > 0.0);
+            let frame = drawn.then_some(atlas.frames.len() as u32);
+            if drawn {
+                atlas.frames.push(AtlasFrameData {
+                    name: glyph.char.to_string(),
+                    rect: glyph.rect,
+                    pivot: Some([0.0, 0.0]),
+                });
+            }
+            glyphs.insert(
+                glyph.char,
+                Glyph {
+                    frame,
+                    offset: Vec2::from(glyph.offset),
+                    advance: glyph.advance,
+                },
+            );
+        }
+        Ok(Self {
+            atlas: SpriteAtlas::from_data(context, &atlas)?,
+            glyphs,
+            fallback: data.fallback,
+            line_height: data.line_height,
+        })
+    }
+
+    /// Gets the glyph shown for a character. Lowercase characters fall
+    /// back to uppercase ones, and then to the fallback glyph.
+    fn glyph(&self, c: char) -> Option<Glyph> {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.glyphs.get(&c.to_ascii_uppercase()))
+            .or_else(|| {
+                self.fallback
+                    .and_then(|fallback| self.glyphs.get(&fallback))
+            })
+            .copied()
+    }
+
+    /// Gets the vertical distance between lines, in pixels.
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// Lays out a text with a certain style. See the text layout
+    /// module for the colour tags accepted on the text.
+    pub fn layout(&self, text: &str, style: &TextStyle) -> TextLayout {
+        TextLayout::new(text, style, self.line_height, |c| self.glyph(c))
+    }
+
+    /// Queues a text layout for drawing, at a certain position.
+    pub fn queue(&mut self, layout: &TextLayout, position: Vec2) -> GameResult {
+        let scale = Vec2::splat(layout.scale());
+        for glyph in layout.glyphs() {
+            self.atlas.queue_draw_tinted(
+                glyph.frame,
+                position + glyph.offset,
+                scale,
+                0.0,
+                glyph.color,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Lays out a text and queues it for drawing, at a certain
+    /// position.
+    pub fn queue_text(&mut self, text: &str, position: Vec2, style: &TextStyle) -> GameResult {
+        let layout = self.layout(text, style);
+        self.queue(&layout, position)
+    }
+
+    /// Clears all queued texts.
+    pub fn clear(&mut self) {
+        self.atlas.clear();
+    }
+
+    /// Draws all queued texts.
+    pub fn draw(&self, context: &mut Context) -> GameResult {
+        self.atlas.draw(context)
+    }
+}
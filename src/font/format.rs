@@ -0,0 +1,84 @@
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Describes a single glyph of a bitmap font.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GlyphData {
+    /// Character shown by the glyph.
+    pub char: char,
+    /// Area of the glyph on the image, as x, y, width and height, in
+    /// pixels. Glyphs with an empty area, such as spaces, are not
+    /// drawn.
+    pub rect: [f32; 4],
+    /// Offset of the glyph from the pen position, in pixels.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset: [f32; 2],
+    /// Horizontal distance from this glyph to the next one, in pixels.
+    pub advance: f32,
+}
+
+fn is_zero(offset: &[f32; 2]) -> bool {
+    *offset == [0.0, 0.0]
+}
+
+/// Describes a bitmap font, as a sidecar file for its glyph image.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FontData {
+    /// Path to the glyph image, relative to the resources directory.
+    pub image: String,
+    /// Vertical distance between lines, in pixels.
+    pub line_height: f32,
+    /// Character shown in place of characters missing from the font,
+    /// if any. Otherwise, missing characters are skipped.
+    #[serde(default)]
+    pub fallback: Option<char>,
+    pub glyphs: Vec<GlyphData>,
+}
+
+pub(crate) fn font_error(name: &str, message: String) -> GameError {
+    GameError::ResourceLoadError(format!("Font {}: {}", name, message))
+}
+
+impl FontData {
+    /// Loads font data from a JSON file on the resources directory.
+    pub fn load(context: &mut Context, path: &str) -> GameResult<Self> {
+        let file = filesystem::open(context, path)?;
+        let data: Self = serde_json::from_reader(file)
+            .map_err(|e| font_error(path, format!("invalid font file: {}", e)))?;
+        data.validate(path)?;
+        Ok(data)
+    }
+
+    /// Checks font data for inconsistencies, such as repeated glyphs
+    /// and a fallback character missing from the font.
+    pub fn validate(&self, name: &str) -> GameResult {
+        if self.line_height <= 0.0 {
+            return Err(font_error(name, "invalid line height".to_string()));
+        }
+        let mut chars = HashSet::new();
+        for glyph in self.glyphs.iter() {
+            if !chars.insert(glyph.char) {
+                return Err(font_error(
+                    name,
+                    format!("glyph {:?} is repeated", glyph.char),
+                ));
+            }
+            if (glyph.rect[2] < 0.0) || (glyph.rect[3] < 0.0) {
+                return Err(font_error(
+                    name,
+                    format!("glyph {:?} has a negative size", glyph.char),
+                ));
+            }
+        }
+        if let Some(fallback) = self.fallback {
+            if !chars.contains(&fallback) {
+                return Err(font_error(
+                    name,
+                    format!("fallback glyph {:?} does not exist", fallback),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use ggez::graphics::Color;
+use glam::*;
+
+/// Enumeration for describing how lines are aligned to the text
+/// position.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    /// Lines start at the text position.
+    #[default]
+    Left,
+    /// Lines are centered on the text position.
+    Center,
+    /// Lines end at the text position.
+    Right,
+}
+
+/// Describes how a text is laid out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    pub align: Align,
+    /// Largest width of a line before words are wrapped to the next
+    /// one, in pixels. Text is only wrapped on line breaks if there is
+    /// no width.
+    pub wrap: Option<f32>,
+    /// Color of the text outside of any colour tags.
+    pub color: Color,
+    /// Scale factor of the text, which should be an integer to keep
+    /// glyphs sharp.
+    pub scale: f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            align: Align::Left,
+            wrap: None,
+            color: Color::WHITE,
+            scale: 1.0,
+        }
+    }
+}
+
+/// Metrics of a single glyph of a font.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Glyph {
+    /// Frame of the glyph on the font atlas, if it is drawn at all.
+    pub frame: Option<u32>,
+    pub offset: Vec2,
+    pub advance: f32,
+}
+
+/// A glyph placed on a text layout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct LayoutGlyph {
+    pub frame: u32,
+    /// Position of the glyph relative to the text position.
+    pub offset: Vec2,
+    pub color: Color,
+}
+
+/// Gets the color of a colour tag, which is either a color name or a
+/// hexadecimal RGB color such as `#ff8000`.
+fn tag_color(tag: &str) -> Option<Color> {
+    if let Some(hex) = tag.strip_prefix('#') {
+        let value = u32::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 6)?;
+        return Some(Color::from_rgb_u32(value));
+    }
+    match tag {
+        "white" => Some(Color::WHITE),
+        "black" => Some(Color::BLACK),
+        "red" => Some(Color::RED),
+        "green" => Some(Color::GREEN),
+        "blue" => Some(Color::BLUE),
+        "yellow" => Some(Color::YELLOW),
+        "cyan" => Some(Color::CYAN),
+        "magenta" => Some(Color::MAGENTA),
+        _ => None,
+    }
+}
+
+/// Splits colour tags from a text, giving the color of each remaining
+/// character.
+///
+/// Tags such as `[red]` or `[#ff8000]` change the color until a
+/// matching `[/]`, and may be nested. A literal bracket is written as
+/// `[[`, and anything which is not a valid tag is kept as it is.
+fn parse_tags(text: &str, color: Color) -> Vec<(char, Color)> {
+    let mut colors = vec![color];
+    let mut chars = Vec::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        let current = *colors.last().unwrap_or(&color);
+        if c != '[' {
+            chars.push((c, current));
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix('[') {
+            chars.push(('[', current));
+            rest = after;
+            continue;
+        }
+        let tag = rest.find(']').map(|end| (&rest[..end], &rest[end + 1..]));
+        match tag {
+            Some(("/", after)) if colors.len() > 1 => {
+                colors.pop();
+                rest = after;
+            }
+            Some((tag, after)) if tag_color(tag).is_some() => {
+                colors.extend(tag_color(tag));
+                rest = after;
+            }
+            _ => chars.push(('[', current)),
+        }
+    }
+    chars
+}
+
+/// Removes the spaces at the end of a line or word.
+fn trim_spaces(chars: &[(char, Color)]) -> &[(char, Color)] {
+    let spaces = chars.iter().rev().take_while(|(c, _)| *c == ' ').count();
+    &chars[..chars.len() - spaces]
+}
+
+/// A text laid out with a font, which can be queued for drawing any
+/// number of times.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLayout {
+    glyphs: Vec<LayoutGlyph>,
+    size: Vec2,
+    scale: f32,
+}
+
+impl TextLayout {
+    /// Lays out a text, given the metrics of each character and the
+    /// height of each line.
+    pub(crate) fn new<F>(text: &str, style: &TextStyle, line_height: f32, glyph: F) -> Self
+    where
+        F: Fn(char) -> Option<Glyph>,
+    {
+        let chars = parse_tags(text, style.color);
+        let width = |chars: &[(char, Color)]| -> f32 {
+            chars
+                .iter()
+                .filter_map(|(c, _)| glyph(*c))
+                .map(|glyph| glyph.advance)
+                .sum()
+        };
+        let wrap = style.wrap.map(|wrap| wrap / style.scale);
+
+        // Break paragraphs into lines, wrapping whole words and
+        // dropping the spaces where lines are wrapped
+        let mut lines: Vec<Vec<(char, Color)>> = Vec::new();
+        for paragraph in chars.split(|(c, _)| *c == '\n') {
+            let mut line: Vec<(char, Color)> = Vec::new();
+            let mut line_width = 0.0;
+            let mut words = paragraph.split_inclusive(|(c, _)| *c == ' ').peekable();
+            if words.peek().is_none() {
+                lines.push(line);
+                continue;
+            }
+            for word in words {
+                if let Some(wrap) = wrap {
+                    if !line.is_empty() && (line_width + width(trim_spaces(word)) > wrap) {
+                        line.truncate(trim_spaces(&line).len());
+                        lines.push(std::mem::take(&mut line));
+                        line_width = 0.0;
+                    }
+                }
+                line.extend_from_slice(word);
+                line_width += width(word);
+            }
+            lines.push(line);
+        }
+
+        // Place glyphs line by line, aligned to the text position
+        let mut glyphs = Vec::new();
+        let mut size = Vec2::ZERO;
+        for (index, line) in lines.iter().enumerate() {
+            let line_width = width(trim_spaces(line));
+            size.x = size.x.max(line_width);
+            let mut pen = glam::vec2(
+                match style.align {
+                    Align::Left => 0.0,
+                    Align::Center => -(line_width / 2.0).round(),
+                    Align::Right => -line_width,
+                },
+                index as f32 * line_height,
+            );
+            for (c, color) in line.iter() {
+                if let Some(glyph) = glyph(*c) {
+                    if let Some(frame) = glyph.frame {
+                        glyphs.push(LayoutGlyph {
+                            frame,
+                            offset: (pen + glyph.offset) * style.scale,
+                            color: *color,
+                        });
+                    }
+                    pen.x += glyph.advance;
+                }
+            }
+        }
+        size.y = lines.len() as f32 * line_height;
+
+        Self {
+            glyphs,
+            size: size * style.scale,
+            scale: style.scale,
+        }
+    }
+
+    /// Gets the size of the text, in pixels.
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Gets the scale factor of the text.
+    pub(crate) fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Gets the placed glyphs of the text.
+    pub(crate) fn glyphs(&self) -> &[LayoutGlyph] {
+        &self.glyphs
+    }
+}
@@ -0,0 +1,3 @@
+pub mod bitmap_font;
+pub mod format;
+pub mod layout;
@@ -2,7 +2,9 @@
 
 use ggez::event::Axis;
 use ggez::event::Button;
+use ggez::event::MouseButton;
 use ggez::input::keyboard::KeyCode;
+use std::collections::HashSet;
 
 const DEADZONE: f32 = 0.3;
 
@@ -25,6 +27,10 @@ pub enum InputButton {
     Back,
     /// The A button.
     A,
+    /// The B button.
+    B,
+    /// The C button.
+    C,
 }
 
 /// A structure describing an input state.
@@ -46,8 +52,19 @@ pub struct InputState {
     pub back: bool,
     /// The state of the A button.
     pub a: bool,
+    /// The state of the B button.
+    pub b: bool,
+    /// The state of the C button.
+    pub c: bool,
     /// The state of the left stick axis.
     pub lstick: (f32, f32),
+    /// Keyboard keys currently held, regardless of the buttons they
+    /// are associated with.
+    pub keys: HashSet<KeyCode>,
+    /// Mouse buttons currently held.
+    pub mouse_buttons: HashSet<MouseButton>,
+    /// Position of the mouse cursor on screen.
+    pub mouse_position: (f32, f32),
 }
 
 /// A structure describing the input system.
@@ -60,6 +77,7 @@ pub struct InputState {
 pub struct Input {
     current: InputState,
     previous: InputState,
+    wheel: f32,
 }
 
 impl Input {
@@ -67,6 +85,7 @@ impl Input {
     /// every frame.
     pub fn post_update(&mut self) {
         self.previous = self.current.clone();
+        self.wheel = 0.0;
     }
 
     fn correct_axes(&mut self) {
@@ -82,6 +101,11 @@ impl Input {
     /// value, with respect to the keyboard key which was
     /// associated with said button.
     pub fn set_keyboard(&mut self, keycode: KeyCode, state: bool) {
+        if state {
+            self.current.keys.insert(keycode);
+        } else {
+            self.current.keys.remove(&keycode);
+        }
         match keycode {
             KeyCode::F1 => self.current.debug = state,
             KeyCode::Up => {
@@ -103,6 +127,8 @@ impl Input {
             KeyCode::Return => self.current.start = state,
             KeyCode::Escape => self.current.back = state,
             KeyCode::Z => self.current.a = state,
+            KeyCode::X => self.current.b = state,
+            KeyCode::C => self.current.c = state,
             _ => {}
         }
         self.correct_axes();
@@ -133,6 +159,8 @@ impl Input {
             Button::Start => self.current.start = state,
             Button::Select => self.current.back = state,
             Button::South => self.current.a = state,
+            Button::East => self.current.b = state,
+            Button::West => self.current.c = state,
             _ => {}
         }
         self.correct_axes();
@@ -184,6 +212,8 @@ impl Input {
             InputButton::Start => self.current.start,
             InputButton::Back => self.current.back,
             InputButton::A => self.current.a,
+            InputButton::B => self.current.b,
+            InputButton::C => self.current.c,
         }
     }
 
@@ -199,6 +229,8 @@ impl Input {
             InputButton::Start => self.current.start && !self.previous.start,
             InputButton::Back => self.current.back && !self.previous.back,
             InputButton::A => self.current.a && !self.previous.a,
+            InputButton::B => self.current.b && !self.previous.b,
+            InputButton::C => self.current.c && !self.previous.c,
         }
     }
 
@@ -206,4 +238,55 @@ impl Input {
     pub fn left_stick(&self) -> (f32, f32) {
         self.current.lstick
     }
+
+    /// Sets the state of the given mouse button.
+    pub fn set_mouse_button(&mut self, button: MouseButton, state: bool) {
+        if state {
+            self.current.mouse_buttons.insert(button);
+        } else {
+            self.current.mouse_buttons.remove(&button);
+        }
+    }
+
+    /// Sets the position of the mouse cursor on screen.
+    pub fn set_mouse_position(&mut self, x: f32, y: f32) {
+        self.current.mouse_position = (x, y);
+    }
+
+    /// Accumulates vertical mouse wheel movement for this frame.
+    pub fn add_wheel(&mut self, y: f32) {
+        self.wheel += y;
+    }
+
+    /// Checks if the given keyboard key is currently being pressed.
+    pub fn key_pressing(&self, keycode: KeyCode) -> bool {
+        self.current.keys.contains(&keycode)
+    }
+
+    /// Checks if the given keyboard key was tapped this frame.
+    pub fn key_pressed(&self, keycode: KeyCode) -> bool {
+        self.current.keys.contains(&keycode) && !self.previous.keys.contains(&keycode)
+    }
+
+    /// Checks if the given mouse button is currently being pressed.
+    pub fn mouse_pressing(&self, button: MouseButton) -> bool {
+        self.current.mouse_buttons.contains(&button)
+    }
+
+    /// Checks if the given mouse button was tapped this frame.
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.current.mouse_buttons.contains(&button)
+            && !self.previous.mouse_buttons.contains(&button)
+    }
+
+    /// Returns the position of the mouse cursor on screen.
+    pub fn mouse_position(&self) -> (f32, f32) {
+        self.current.mouse_position
+    }
+
+    /// Returns how much the mouse wheel moved this frame. Positive
+    /// values mean moving away from the user.
+    pub fn wheel(&self) -> f32 {
+        self.wheel
+    }
 }
@@ -0,0 +1,218 @@
+use ggez::graphics::spritebatch::SpriteBatch;
+use ggez::graphics::{self, Color, DrawMode, DrawParam, FilterMode, Image, MeshBuilder, Rect};
+use ggez::{filesystem, Context, GameError, GameResult};
+use glam::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// Describes a horizontal deformation applied to a background layer,
+/// such as water shimmer or heat haze.
+///
+/// The layer is split into horizontal strips, and each strip is
+/// offset following a sine wave which moves over time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeformationData {
+    /// Height of each strip, in pixels. Use 1 for per-line deformation.
+    pub strip_height: u32,
+    /// Maximum horizontal offset of a strip, in pixels.
+    pub amplitude: f32,
+    /// Vertical length of a full wave, in pixels.
+    pub period: f32,
+    /// How much the wave moves on each frame, in radians.
+    pub speed: f32,
+    /// First line of the image which is deformed. Lines above it are
+    /// left untouched.
+    #[serde(default)]
+    pub start: u32,
+}
+
+/// Describes a single layer of a background.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackgroundLayerData {
+    /// Path to the layer image, relative to the resources directory.
+    pub image: String,
+    /// Factor of the camera movement applied to the layer. A factor
+    /// of 0 stays still, and a factor of 1 moves with the level.
+    pub scroll: [f32; 2],
+    /// Position of the layer when the camera is at the origin.
+    #[serde(default)]
+    pub offset: [f32; 2],
+    /// Movement of the layer on each frame, in pixels.
+    #[serde(default)]
+    pub auto_scroll: [f32; 2],
+    /// Whether the layer repeats horizontally.
+    #[serde(default)]
+    pub wrap: bool,
+    /// Deformation of the layer, if any.
+    #[serde(default)]
+    pub deformation: Option<DeformationData>,
+}
+
+/// Describes a background, as layers drawn from back to front.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackgroundData {
+    /// Color drawn behind all layers, as RGBA.
+    #[serde(default)]
+    pub color: Option<[f32; 4]>,
+    pub layers: Vec<BackgroundLayerData>,
+}
+
+impl BackgroundData {
+    /// Loads a background description from a JSON file on the
+    /// resources directory.
+    pub fn load(context: &mut Context, path: &str) -> GameResult<Self> {
+        let file = filesystem::open(context, path)?;
+        let data: Self = serde_json::from_reader(file)
+            .map_err(|e| GameError::ResourceLoadError(format!("Background {}: {}", path, e)))?;
+        if let Some(layer) = data.layers.iter().find(|layer| {
+            layer
+                .deformation
+                .as_ref()
+                .map(|d| (d.strip_height == 0) || (d.period <= 0.0))
+                .unwrap_or(false)
+        }) {
+            return Err(GameError::ResourceLoadError(format!(
+                "Background {}: layer {} has an invalid deformation",
+                path, layer.image
+            )));
+        }
+        Ok(data)
+    }
+}
+
+/// A background layer, along with its image and scrolling state.
+#[derive(Debug, Clone, PartialEq)]
+struct BackgroundLayer {
+    data: BackgroundLayerData,
+    texture: Image,
+    batch: SpriteBatch,
+    scrolled: Vec2,
+    phase: f32,
+}
+
+impl BackgroundLayer {
+    /// Queues a horizontal strip of the layer image, repeating it
+    /// horizontally if the layer wraps.
+    fn queue_strip(&mut self, position: Vec2, line: u32, height: u32, width: f32) {
+        let size = glam::vec2(self.texture.width() as f32, self.texture.height() as f32);
+        let source = Rect::new(0.0, line as f32 / size.y, 1.0, height as f32 / size.y);
+        let y = (position.y + line as f32).round();
+
+        if self.data.wrap {
+            let mut x = position.x.rem_euclid(size.x) - size.x;
+            while x < width {
+                self.batch.add(
+                    DrawParam::default()
+                        .src(source)
+                        .dest(glam::vec2(x.round(), y)),
+                );
+                x += size.x;
+            }
+        } else {
+            self.batch.add(
+                DrawParam::default()
+                    .src(source)
+                    .dest(glam::vec2(position.x.round(), y)),
+            );
+        }
+    }
+
+    /// Queues the layer as seen from a certain area of the level.
+    fn queue(&mut self, view: Rect) {
+        self.batch.clear();
+        let scroll = glam::vec2(self.data.scroll[0], self.data.scroll[1]);
+        let offset = glam::vec2(self.data.offset[0], self.data.offset[1]);
+        let position = offset + self.scrolled - glam::vec2(view.x, view.y) * scroll;
+        let height = self.texture.height() as u32;
+
+        let deformation = match self.data.deformation.clone() {
+            Some(deformation) => deformation,
+            None => {
+                self.queue_strip(position, 0, height, view.w);
+                return;
+            }
+        };
+
+        let start = deformation.start.min(height);
+        if start > 0 {
+            self.queue_strip(position, 0, start, view.w);
+        }
+        let mut line = start;
+        while line < height {
+            let strip = deformation.strip_height.min(height - line);
+            let wave = self.phase + (line as f32 / deformation.period) * PI * 2.0;
+            let shift = glam::vec2(wave.sin() * deformation.amplitude, 0.0);
+            self.queue_strip(position + shift, line, strip, view.w);
+            line += strip;
+        }
+    }
+}
+
+/// Represents the background drawn behind a level, made of several
+/// layers which scroll at their own rate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Background {
+    color: Option<Color>,
+    layers: Vec<BackgroundLayer>,
+}
+
+impl Background {
+    /// Creates a background from its description, loading the images
+    /// of its layers.
+    pub fn new(context: &mut Context, data: &BackgroundData) -> GameResult<Self> {
+        let mut layers = Vec::with_capacity(data.layers.len());
+        for layer in data.layers.iter() {
+            let mut texture = Image::new(context, &layer.image)?;
+            texture.set_filter(FilterMode::Nearest);
+            layers.push(BackgroundLayer {
+                data: layer.clone(),
+                batch: SpriteBatch::new(texture.clone()),
+                texture,
+                scrolled: Vec2::ZERO,
+                phase: 0.0,
+            });
+        }
+        Ok(Self {
+            color: data.color.map(Color::from),
+            layers,
+        })
+    }
+
+    /// Updates the automatic scrolling and deformation of all layers.
+    /// Should be called once per frame.
+    pub fn update(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.scrolled += glam::vec2(layer.data.auto_scroll[0], layer.data.auto_scroll[1]);
+            if layer.data.wrap {
+                layer.scrolled.x = layer.scrolled.x.rem_euclid(layer.texture.width() as f32);
+            }
+            if let Some(deformation) = &layer.data.deformation {
+                layer.phase = (layer.phase + deformation.speed) % (PI * 2.0);
+            }
+        }
+    }
+
+    /// Queues all layers as seen from a certain area of the level.
+    /// Should be called after the camera is updated.
+    pub fn queue(&mut self, view: Rect) {
+        for layer in self.layers.iter_mut() {
+            layer.queue(view);
+        }
+    }
+
+    /// Draws the queued layers, filling the screen with the background
+    /// color first.
+    pub fn draw(&self, context: &mut Context) -> GameResult {
+        if let Some(color) = self.color {
+            let screen = graphics::screen_coordinates(context);
+            let mesh = MeshBuilder::new()
+                .rectangle(DrawMode::fill(), screen, color)?
+                .build(context)?;
+            graphics::draw(context, &mesh, DrawParam::default())?;
+        }
+        for layer in self.layers.iter() {
+            graphics::draw(context, &layer.batch, DrawParam::default())?;
+        }
+        Ok(())
+    }
+}
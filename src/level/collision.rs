@@ -0,0 +1,297 @@
+use ggez::graphics::{self, Color, DrawMode, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use glam::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+/// Size of a single tile, in pixels.
+pub const TILE_SIZE: usize = 16;
+
+/// Maximum distance a sensor regresses to find a surface when it
+/// starts inside of solid terrain, in pixels.
+const MAX_REGRESSION: usize = 32;
+
+/// Describes the collision layer (or path) an object is on.
+///
+/// Each tile may have a different solidity on each layer, which
+/// allows the terrain to overlap itself, as it happens on loops.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CollisionLayer {
+    #[default]
+    A,
+    B,
+}
+
+/// Represents the solid shape of a tile.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeightMask {
+    /// Solid height of each column of the tile, measured from its
+    /// bottom, in pixels.
+    pub heights: [u8; TILE_SIZE],
+    /// Angle of the surface of the tile, in radians. If not present,
+    /// the angle snaps to the direction of whatever sensor finds it,
+    /// which is what is expected of fully solid blocks.
+    pub angle: Option<f32>,
+}
+
+impl HeightMask {
+    /// Checks whether a pixel of the tile is solid.
+    pub fn solid_at(&self, x: usize, y: usize) -> bool {
+        let height = self.heights[x.min(TILE_SIZE - 1)] as usize;
+        y >= TILE_SIZE - height.min(TILE_SIZE)
+    }
+}
+
+/// Refers to the height masks used by a tile on each layer.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct TileCollision {
+    /// Height mask index used on layer A.
+    pub a: Option<u16>,
+    /// Height mask index used on layer B.
+    pub b: Option<u16>,
+    /// Whether the height masks are flipped horizontally.
+    pub flip_x: bool,
+    /// Whether the height masks are flipped vertically.
+    pub flip_y: bool,
+}
+
+impl TileCollision {
+    /// Gets the height mask index used on a specific layer.
+    pub fn on(&self, layer: CollisionLayer) -> Option<u16> {
+        match layer {
+            CollisionLayer::A => self.a,
+            CollisionLayer::B => self.b,
+        }
+    }
+
+    /// Converts a pixel position inside the tile into a position on
+    /// its height mask, taking flipping into account.
+    fn local(&self, x: usize, y: usize) -> (usize, usize) {
+        (
+            if self.flip_x { TILE_SIZE - 1 - x } else { x },
+            if self.flip_y { TILE_SIZE - 1 - y } else { y },
+        )
+    }
+
+    /// Gets the surface angle of a height mask placed on this tile,
+    /// taking flipping into account.
+    fn angle(&self, mask: &HeightMask) -> Option<f32> {
+        mask.angle.map(|angle| {
+            let angle = match (self.flip_x, self.flip_y) {
+                (false, false) => angle,
+                (true, false) => -angle,
+                (false, true) => PI - angle,
+                (true, true) => PI + angle,
+            };
+            angle.rem_euclid(PI * 2.0)
+        })
+    }
+}
+
+/// Describes the direction a sensor is pointing to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SensorDirection {
+    Down,
+    Right,
+    Up,
+    Left,
+}
+
+impl SensorDirection {
+    /// Gets a unit vector for this direction.
+    pub fn vector(&self) -> Vec2 {
+        match self {
+            SensorDirection::Down => glam::vec2(0.0, 1.0),
+            SensorDirection::Right => glam::vec2(1.0, 0.0),
+            SensorDirection::Up => glam::vec2(0.0, -1.0),
+            SensorDirection::Left => glam::vec2(-1.0, 0.0),
+        }
+    }
+
+    /// Gets the opposite direction.
+    pub fn opposite(&self) -> Self {
+        match self {
+            SensorDirection::Down => SensorDirection::Up,
+            SensorDirection::Right => SensorDirection::Left,
+            SensorDirection::Up => SensorDirection::Down,
+            SensorDirection::Left => SensorDirection::Right,
+        }
+    }
+
+    /// Gets the angle of a surface found by a sensor pointing to
+    /// this direction, when the surface has no angle of its own.
+    fn snapped_angle(&self) -> f32 {
+        match self {
+            SensorDirection::Down => 0.0,
+            SensorDirection::Right => PI / 2.0,
+            SensorDirection::Up => PI,
+            SensorDirection::Left => PI * 1.5,
+        }
+    }
+}
+
+/// Result of a sensor which found a surface.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SensorHit {
+    /// Distance from the sensor origin to the surface, in pixels.
+    /// Negative distances mean that the origin is inside the terrain.
+    pub distance: f32,
+    /// Angle of the surface, in radians.
+    pub angle: f32,
+}
+
+/// Represents the solid terrain of a level, as a grid of tiles.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollisionMap {
+    width: usize,
+    height: usize,
+    tiles: Vec<TileCollision>,
+    masks: Vec<HeightMask>,
+}
+
+impl CollisionMap {
+    /// Creates an empty collision map with a size in tiles, and the
+    /// height masks its tiles may refer to.
+    pub fn new(width: usize, height: usize, masks: Vec<HeightMask>) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![TileCollision::default(); width * height],
+            masks,
+        }
+    }
+
+    /// Gets the collision of a tile. Tiles outside the map are empty.
+    pub fn get(&self, x: usize, y: usize) -> TileCollision {
+        if (x < self.width) && (y < self.height) {
+            self.tiles[y * self.width + x]
+        } else {
+            TileCollision::default()
+        }
+    }
+
+    /// Sets the collision of a tile. Tiles outside the map are ignored.
+    pub fn set(&mut self, x: usize, y: usize, tile: TileCollision) {
+        if (x < self.width) && (y < self.height) {
+            self.tiles[y * self.width + x] = tile;
+        }
+    }
+
+    /// Finds the tile and height mask containing a point on a layer,
+    /// along with the position of the point on the height mask.
+    fn mask_at(
+        &self,
+        layer: CollisionLayer,
+        point: Vec2,
+    ) -> Option<(TileCollision, &HeightMask, usize, usize)> {
+        if (point.x < 0.0) || (point.y < 0.0) {
+            return None;
+        }
+        let (x, y) = (point.x as usize, point.y as usize);
+        let tile = self.get(x / TILE_SIZE, y / TILE_SIZE);
+        let mask = self.masks.get(tile.on(layer)? as usize)?;
+        let (x, y) = tile.local(x % TILE_SIZE, y % TILE_SIZE);
+        Some((tile, mask, x, y))
+    }
+
+    /// Checks whether a point is inside solid terrain on a layer.
+    pub fn solid_at(&self, layer: CollisionLayer, point: Vec2) -> bool {
+        self.mask_at(layer, point)
+            .map(|(_, mask, x, y)| mask.solid_at(x, y))
+            .unwrap_or(false)
+    }
+
+    /// Casts a sensor from a point on a layer, looking for the closest
+    /// surface towards a direction, up to a maximum distance.
+    pub fn sense(
+        &self,
+        layer: CollisionLayer,
+        origin: Vec2,
+        direction: SensorDirection,
+        max: f32,
+    ) -> Option<SensorHit> {
+        let step = direction.vector();
+        let origin = origin.floor();
+
+        let (distance, surface) = if self.solid_at(layer, origin) {
+            // Regress until finding an empty pixel
+            let back = direction.opposite().vector();
+            let steps =
+                (1..=MAX_REGRESSION).find(|&i| !self.solid_at(layer, origin + back * i as f32))?;
+            (-(steps as f32) + 1.0, origin + back * (steps - 1) as f32)
+        } else {
+            // Extend until finding a solid pixel
+            let steps = (1..=max.max(0.0) as usize)
+                .find(|&i| self.solid_at(layer, origin + step * i as f32))?;
+            (steps as f32, origin + step * steps as f32)
+        };
+
+        let (tile, mask, _, _) = self.mask_at(layer, surface)?;
+        Some(SensorHit {
+            distance,
+            angle: tile
+                .angle(mask)
+                .unwrap_or_else(|| direction.snapped_angle()),
+        })
+    }
+
+    /// Draws the solid terrain of a layer which is visible on a certain
+    /// area of the level, for debugging purposes.
+    pub fn debug_draw<F>(
+        &self,
+        context: &mut Context,
+        layer: CollisionLayer,
+        view: Rect,
+        transform: F,
+    ) -> GameResult
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        let tile = TILE_SIZE as f32;
+        let first = (view.x / tile).floor().max(0.0) as usize;
+        let last = (view.right() / tile).ceil().max(0.0) as usize;
+        let top = (view.y / tile).floor().max(0.0) as usize;
+        let bottom = (view.bottom() / tile).ceil().max(0.0) as usize;
+
+        let mut builder = MeshBuilder::new();
+        let mut empty = true;
+        for y in top..=bottom.min(self.height) {
+            for x in first..=last.min(self.width) {
+                let tile_collision = self.get(x, y);
+                let mask = match tile_collision
+                    .on(layer)
+                    .and_then(|i| self.masks.get(i as usize))
+                {
+                    Some(mask) => mask,
+                    None => continue,
+                };
+                let corner = transform(glam::vec2((x * TILE_SIZE) as f32, (y * TILE_SIZE) as f32));
+                for column in 0..TILE_SIZE {
+                    let (source, _) = tile_collision.local(column, 0);
+                    let height = mask.heights[source];
+                    if height == 0 {
+                        continue;
+                    }
+                    let height = (height as f32).min(tile);
+                    let top = if tile_collision.flip_y {
+                        0.0
+                    } else {
+                        tile - height
+                    };
+                    builder.rectangle(
+                        DrawMode::fill(),
+                        Rect::new(corner.x + column as f32, corner.y + top, 1.0, height),
+                        Color::new(1.0, 1.0, 1.0, 0.3),
+                    )?;
+                    empty = false;
+                }
+            }
+        }
+
+        if !empty {
+            let mesh = builder.build(context)?;
+            graphics::draw(context, &mesh, (Vec2::ZERO, 0.0, Color::WHITE))?;
+        }
+        Ok(())
+    }
+}
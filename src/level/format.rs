@@ -0,0 +1,843 @@
+use super::collision::{CollisionLayer, CollisionMap, HeightMask, TileCollision, TILE_SIZE};
+use super::tiled::{self, TiledFormat};
+use crate::objects::arena::ArenaLock;
+use crate::objects::camera::{CameraBounds, CameraRegion};
+use crate::objects::checkpoint::Checkpoint;
+use crate::objects::general::{Position, Priority};
+use crate::objects::hazards::{Hazard, HazardKind, SpikeDirection, Spikes};
+use crate::objects::object_manager::{ObjectKind, ObjectPlacement};
+use crate::objects::path_swapper::{PathSwapper, SwapperOrientation};
+use crate::objects::signpost::Signpost;
+use ggez::graphics::Rect;
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Current version of the level file format.
+pub const LEVEL_FORMAT_VERSION: u32 = 2;
+
+/// Size of a chunk side, in tiles.
+pub const CHUNK_TILES: usize = 8;
+
+/// Size of a chunk side, in pixels.
+pub const CHUNK_SIZE: usize = CHUNK_TILES * TILE_SIZE;
+
+/// Describes the solid shape of a tile on a level file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MaskData {
+    /// Solid height of each column, from the bottom of the tile.
+    pub heights: [u8; TILE_SIZE],
+    /// Angle of the surface, in degrees. Fully solid blocks should
+    /// have no angle.
+    #[serde(default)]
+    pub angle: Option<f32>,
+}
+
+/// Describes a single frame of an animated tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileFrameData {
+    /// Index of the tileset graphics shown on this frame.
+    pub tile: u16,
+    /// Duration of the frame, in game frames.
+    pub duration: u32,
+}
+
+/// Describes a 16x16 tile on a level file.
+///
+/// The graphics of a tile are taken from the tileset image, by
+/// using the index of the tile itself, unless the tile is animated.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TileData {
+    /// Height mask used on collision layer A.
+    #[serde(default)]
+    pub collision_a: Option<u16>,
+    /// Height mask used on collision layer B.
+    #[serde(default)]
+    pub collision_b: Option<u16>,
+    /// Frames of the tile animation, which loops through them. Tiles
+    /// without frames are not animated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub animation: Vec<TileFrameData>,
+}
+
+/// Refers to a tile placed on a chunk, which may be flipped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileRef {
+    /// Index of the tile.
+    pub tile: u16,
+    /// Whether the tile is flipped horizontally.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub flip_x: bool,
+    /// Whether the tile is flipped vertically.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub flip_y: bool,
+    /// Whether the tile is drawn in front of low priority sprites.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub high_priority: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl TileRef {
+    /// Creates a reference to a tile which is not flipped.
+    pub fn new(tile: u16) -> Self {
+        Self {
+            tile,
+            ..Self::default()
+        }
+    }
+}
+
+/// Describes a 128x128 chunk on a level file, as a grid of tiles
+/// in the left-right, top-bottom order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChunkData {
+    pub tiles: Vec<Option<TileRef>>,
+}
+
+/// Describes a layout of chunks on a level file, in the left-right,
+/// top-bottom order.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayoutData {
+    /// Width of the layout, in chunks.
+    pub width: usize,
+    /// Height of the layout, in chunks.
+    pub height: usize,
+    /// Chunk index on each position of the layout.
+    pub chunks: Vec<Option<u16>>,
+}
+
+impl LayoutData {
+    /// Gets the chunk on a certain position of the layout.
+    pub fn get(&self, x: usize, y: usize) -> Option<u16> {
+        if (x < self.width) && (y < self.height) {
+            self.chunks.get(y * self.width + x).copied().flatten()
+        } else {
+            None
+        }
+    }
+}
+
+/// Describes a kind of object on a level file, and its parameters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ObjectKindData {
+    Spikes {
+        direction: SpikeDirection,
+        width: f32,
+        height: f32,
+    },
+    Hazard {
+        hazard: HazardKind,
+        width: f32,
+        height: f32,
+    },
+    Checkpoint {
+        index: u32,
+    },
+    Signpost,
+    PathSwapper {
+        orientation: SwapperOrientation,
+        length: f32,
+        layer_after: CollisionLayer,
+        layer_before: CollisionLayer,
+        priority_after: Priority,
+        priority_before: Priority,
+        #[serde(default)]
+        ground_only: bool,
+    },
+    ArenaLock {
+        width: f32,
+        height: f32,
+        min_x: f32,
+        min_y: f32,
+        max_x: f32,
+        max_y: f32,
+    },
+}
+
+/// Describes the placement of an object on a level file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObjectData {
+    pub x: f32,
+    pub y: f32,
+    #[serde(flatten)]
+    pub kind: ObjectKindData,
+}
+
+/// Describes the area the camera is allowed to show.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoundsData {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl BoundsData {
+    /// Converts bounds data into camera bounds.
+    pub fn to_camera_bounds(&self) -> CameraBounds {
+        CameraBounds {
+            min: glam::vec2(self.min[0], self.min[1]),
+            max: glam::vec2(self.max[0], self.max[1]),
+        }
+    }
+}
+
+/// Describes a region of the level with its own camera bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraRegionData {
+    /// Area covered by the region.
+    pub area: BoundsData,
+    /// Camera bounds used while players are inside the region.
+    pub bounds: BoundsData,
+}
+
+/// Represents the contents of a level file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LevelData {
+    /// Version of the level format.
+    pub version: u32,
+    /// Name of the level.
+    pub name: String,
+    /// Path to the tileset image, relative to the resources directory.
+    pub tileset: String,
+    /// Path to the palettes of the tileset image, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<String>,
+    /// Height masks used by tiles.
+    pub masks: Vec<MaskData>,
+    /// Tile definitions.
+    pub tiles: Vec<TileData>,
+    /// Chunk definitions.
+    pub chunks: Vec<ChunkData>,
+    /// Layout of the foreground plane, which also defines collision.
+    pub foreground: LayoutData,
+    /// Layout of the background plane.
+    #[serde(default)]
+    pub background: LayoutData,
+    /// Object placements.
+    #[serde(default)]
+    pub objects: Vec<ObjectData>,
+    /// Position where players start the level.
+    pub start: [f32; 2],
+    /// Camera bounds used outside of camera regions.
+    pub camera_bounds: BoundsData,
+    /// Regions of the level with their own camera bounds. If regions
+    /// overlap, the first one wins.
+    #[serde(default)]
+    pub camera_regions: Vec<CameraRegionData>,
+}
+
+fn level_error(name: &str, message: String) -> GameError {
+    GameError::ResourceLoadError(format!("Level {}: {}", name, message))
+}
+
+impl MaskData {
+    /// Converts mask data into a height mask, with its angle in radians.
+    pub fn to_height_mask(&self) -> HeightMask {
+        HeightMask {
+            heights: self.heights,
+            angle: self.angle.map(f32::to_radians),
+        }
+    }
+}
+
+impl ObjectData {
+    /// Converts object data into an object placement for the
+    /// object manager.
+    pub fn to_placement(&self) -> ObjectPlacement {
+        let kind = match &self.kind {
+            ObjectKindData::Spikes {
+                direction,
+                width,
+                height,
+            } => ObjectKind::Spikes(Spikes {
+                direction: *direction,
+                size: glam::vec2(*width, *height),
+            }),
+            ObjectKindData::Hazard {
+                hazard,
+                width,
+                height,
+            } => ObjectKind::Hazard(Hazard {
+                kind: *hazard,
+                size: glam::vec2(*width, *height),
+            }),
+            ObjectKindData::Checkpoint { index } => ObjectKind::Checkpoint(Checkpoint {
+                index: *index,
+                ..Checkpoint::default()
+            }),
+            ObjectKindData::Signpost => ObjectKind::Signpost(Signpost::default()),
+            ObjectKindData::PathSwapper {
+                orientation,
+                length,
+                layer_after,
+                layer_before,
+                priority_after,
+                priority_before,
+                ground_only,
+            } => ObjectKind::PathSwapper(PathSwapper {
+                orientation: *orientation,
+                length: *length,
+                layer_after: *layer_after,
+                layer_before: *layer_before,
+                priority_after: *priority_after,
+                priority_before: *priority_before,
+                ground_only: *ground_only,
+            }),
+            ObjectKindData::ArenaLock {
+                width,
+                height,
+                min_x,
+                min_y,
+                max_x,
+                max_y,
+            } => ObjectKind::ArenaLock(ArenaLock {
+                size: glam::vec2(*width, *height),
+                bounds: CameraBounds {
+                    min: glam::vec2(*min_x, *min_y),
+                    max: glam::vec2(*max_x, *max_y),
+                },
+                triggered: false,
+            }),
+        };
+        ObjectPlacement {
+            kind,
+            position: Position::new(self.x, self.y),
+        }
+    }
+
+    /// Converts an object placement back into object data, so that
+    /// it can be saved on a level file.
+    pub fn from_placement(placement: &ObjectPlacement) -> Self {
+        let kind = match placement.kind {
+            ObjectKind::Spikes(spikes) => ObjectKindData::Spikes {
+                direction: spikes.direction,
+                width: spikes.size.x,
+                height: spikes.size.y,
+            },
+            ObjectKind::Hazard(hazard) => ObjectKindData::Hazard {
+                hazard: hazard.kind,
+                width: hazard.size.x,
+                height: hazard.size.y,
+            },
+            ObjectKind::Checkpoint(checkpoint) => ObjectKindData::Checkpoint {
+                index: checkpoint.index,
+            },
+            ObjectKind::Signpost(_) => ObjectKindData::Signpost,
+            ObjectKind::PathSwapper(swapper) => ObjectKindData::PathSwapper {
+                orientation: swapper.orientation,
+                length: swapper.length,
+                layer_after: swapper.layer_after,
+                layer_before: swapper.layer_before,
+                priority_after: swapper.priority_after,
+                priority_before: swapper.priority_before,
+                ground_only: swapper.ground_only,
+            },
+            ObjectKind::ArenaLock(arena) => ObjectKindData::ArenaLock {
+                width: arena.size.x,
+                height: arena.size.y,
+                min_x: arena.bounds.min.x,
+                min_y: arena.bounds.min.y,
+                max_x: arena.bounds.max.x,
+                max_y: arena.bounds.max.y,
+            },
+        };
+        Self {
+            x: placement.position.0.x,
+            y: placement.position.0.y,
+            kind,
+        }
+    }
+}
+
+impl LevelData {
+    /// Loads level data from a file on the resources directory.
+    ///
+    /// Tiled maps (.tmx and .tmj files) are imported on the fly, and
+    /// any other file is expected to be a level in JSON format.
+    pub fn load(context: &mut Context, path: &str) -> GameResult<Self> {
+        if TiledFormat::from_path(path).is_some() {
+            return tiled::import(path, |path| {
+                let mut contents = String::new();
+                filesystem::open(context, path)?.read_to_string(&mut contents)?;
+                Ok(contents)
+            });
+        }
+        let file = filesystem::open(context, path)?;
+        Self::read(path, file)
+    }
+
+    /// Saves level data as a JSON file on disk.
+    ///
+    /// The path is not relative to the resources directory, since
+    /// files saved on the user data directory would shadow the ones
+    /// on the resources directory.
+    pub fn save(&self, path: &Path) -> GameResult {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = File::create(path)?;
+        self.write(file)
+    }
+
+    /// Reads level data in JSON format, checking it for errors. Files
+    /// from older versions of the format are upgraded on the fly.
+    ///
+    /// The name is only used to describe errors.
+    pub fn read<R: Read>(name: &str, reader: R) -> GameResult<Self> {
+        let invalid =
+            |e: serde_json::Error| level_error(name, format!("invalid level file: {}", e));
+        let value = serde_json::from_reader(reader).map_err(invalid)?;
+        let data: Self = Self::upgrade(value)
+            .and_then(serde_json::from_value)
+            .map_err(invalid)?;
+        data.validate(name)?;
+        Ok(data)
+    }
+
+    /// Upgrades a level in JSON format from older versions of the
+    /// format to the current one.
+    ///
+    /// Version 1 refers to the tiles of chunks by their index alone,
+    /// which become references to tiles that are not flipped.
+    fn upgrade(mut value: Value) -> serde_json::Result<Value> {
+        if value.get("version").and_then(Value::as_u64) != Some(1) {
+            return Ok(value);
+        }
+        if let Some(chunks) = value.get_mut("chunks").and_then(Value::as_array_mut) {
+            for chunk in chunks.iter_mut() {
+                if let Some(tiles) = chunk.get_mut("tiles") {
+                    let indices: Vec<Option<u16>> = serde_json::from_value(tiles.take())?;
+                    let refs: Vec<Option<TileRef>> = indices
+                        .into_iter()
+                        .map(|tile| tile.map(TileRef::new))
+                        .collect();
+                    *tiles = serde_json::to_value(refs)?;
+                }
+            }
+        }
+        value["version"] = Value::from(LEVEL_FORMAT_VERSION);
+        Ok(value)
+    }
+
+    /// Writes level data in JSON format.
+    pub fn write<W: Write>(&self, writer: W) -> GameResult {
+        serde_json::to_writer_pretty(writer, self)
+            .map_err(|e| level_error(&self.name, format!("could not write level: {}", e)))
+    }
+
+    /// Checks level data for inconsistencies, such as references to
+    /// nonexistent tiles and chunks.
+    pub fn validate(&self, name: &str) -> GameResult {
+        if self.version != LEVEL_FORMAT_VERSION {
+            return Err(level_error(
+                name,
+                format!(
+                    "unsupported format version {} (expected {})",
+                    self.version, LEVEL_FORMAT_VERSION
+                ),
+            ));
+        }
+
+        for (i, mask) in self.masks.iter().enumerate() {
+            if let Some(height) = mask.heights.iter().find(|&&h| h as usize > TILE_SIZE) {
+                return Err(level_error(
+                    name,
+                    format!("mask {} has height {} above {}", i, height, TILE_SIZE),
+                ));
+            }
+        }
+
+        for (i, tile) in self.tiles.iter().enumerate() {
+            if tile.animation.iter().any(|frame| frame.duration == 0) {
+                return Err(level_error(
+                    name,
+                    format!("tile {} has an animation frame with no duration", i),
+                ));
+            }
+            for (layer, mask) in [("A", tile.collision_a), ("B", tile.collision_b)] {
+                if let Some(mask) = mask {
+                    if mask as usize >= self.masks.len() {
+                        return Err(level_error(
+                            name,
+                            format!(
+                                "tile {} refers to missing mask {} on layer {}",
+                                i, mask, layer
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            if chunk.tiles.len() != CHUNK_TILES * CHUNK_TILES {
+                return Err(level_error(
+                    name,
+                    format!(
+                        "chunk {} has {} tiles (expected {})",
+                        i,
+                        chunk.tiles.len(),
+                        CHUNK_TILES * CHUNK_TILES
+                    ),
+                ));
+            }
+            if let Some(tile) = chunk
+                .tiles
+                .iter()
+                .flatten()
+                .find(|tile| tile.tile as usize >= self.tiles.len())
+            {
+                return Err(level_error(
+                    name,
+                    format!("chunk {} refers to missing tile {}", i, tile.tile),
+                ));
+            }
+        }
+
+        for (plane, layout) in [
+            ("foreground", &self.foreground),
+            ("background", &self.background),
+        ] {
+            if layout.chunks.len() != layout.width * layout.height {
+                return Err(level_error(
+                    name,
+                    format!(
+                        "{} layout has {} chunks (expected {}x{})",
+                        plane,
+                        layout.chunks.len(),
+                        layout.width,
+                        layout.height
+                    ),
+                ));
+            }
+            if let Some(chunk) = layout
+                .chunks
+                .iter()
+                .flatten()
+                .find(|&&chunk| chunk as usize >= self.chunks.len())
+            {
+                return Err(level_error(
+                    name,
+                    format!("{} layout refers to missing chunk {}", plane, chunk),
+                ));
+            }
+        }
+
+        for (i, region) in self.camera_regions.iter().enumerate() {
+            for bounds in [&region.area, &region.bounds] {
+                if (bounds.min[0] > bounds.max[0]) || (bounds.min[1] > bounds.max[1]) {
+                    return Err(level_error(
+                        name,
+                        format!("camera region {} has its minimum above its maximum", i),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Numbers checkpoints from 1 on the order they appear on the
+    /// level, from left to right.
+    pub fn renumber_checkpoints(&mut self) {
+        let mut checkpoints: Vec<&mut ObjectData> = self
+            .objects
+            .iter_mut()
+            .filter(|object| matches!(object.kind, ObjectKindData::Checkpoint { .. }))
+            .collect();
+        checkpoints.sort_by(|a, b| a.x.total_cmp(&b.x));
+        for (index, object) in checkpoints.into_iter().enumerate() {
+            object.kind = ObjectKindData::Checkpoint {
+                index: index as u32 + 1,
+            };
+        }
+    }
+
+    /// Gets the tile on a certain tile position of a layout.
+    pub fn tile_at(&self, layout: &LayoutData, x: usize, y: usize) -> Option<TileRef> {
+        let chunk = layout.get(x / CHUNK_TILES, y / CHUNK_TILES)?;
+        self.chunks
+            .get(chunk as usize)?
+            .tiles
+            .get((y % CHUNK_TILES) * CHUNK_TILES + (x % CHUNK_TILES))
+            .copied()
+            .flatten()
+    }
+
+    /// Builds the collision map of the level from its foreground layout.
+    pub fn collision_map(&self) -> CollisionMap {
+        let width = self.foreground.width * CHUNK_TILES;
+        let height = self.foreground.height * CHUNK_TILES;
+        let masks = self.masks.iter().map(MaskData::to_height_mask).collect();
+        let mut map = CollisionMap::new(width, height, masks);
+        for y in 0..height {
+            for x in 0..width {
+                if let Some(tile_ref) = self.tile_at(&self.foreground, x, y) {
+                    let tile = &self.tiles[tile_ref.tile as usize];
+                    map.set(
+                        x,
+                        y,
+                        TileCollision {
+                            a: tile.collision_a,
+                            b: tile.collision_b,
+                            flip_x: tile_ref.flip_x,
+                            flip_y: tile_ref.flip_y,
+                        },
+                    );
+                }
+            }
+        }
+        map
+    }
+
+    /// Builds the object layout of the level.
+    pub fn object_layout(&self) -> Vec<ObjectPlacement> {
+        self.objects.iter().map(ObjectData::to_placement).collect()
+    }
+
+    /// Gets the position where players start the level.
+    pub fn start_position(&self) -> Position {
+        Position::new(self.start[0], self.start[1])
+    }
+
+    /// Gets the camera bounds used outside of camera regions.
+    pub fn camera_bounds(&self) -> CameraBounds {
+        self.camera_bounds.to_camera_bounds()
+    }
+
+    /// Builds the camera regions of the level.
+    pub fn camera_regions(&self) -> Vec<CameraRegion> {
+        self.camera_regions
+            .iter()
+            .map(|region| {
+                let area = region.area.to_camera_bounds();
+                CameraRegion {
+                    area: Rect::new(
+                        area.min.x,
+                        area.min.y,
+                        area.max.x - area.min.x,
+                        area.max.y - area.min.y,
+                    ),
+                    bounds: region.bounds.to_camera_bounds(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a small level using every part of the format.
+    fn sample_level() -> LevelData {
+        let mut tiles = vec![None; CHUNK_TILES * CHUNK_TILES];
+        tiles[0] = Some(TileRef {
+            tile: 0,
+            ..TileRef::default()
+        });
+        tiles[1] = Some(TileRef {
+            tile: 1,
+            flip_x: true,
+            flip_y: false,
+            high_priority: true,
+        });
+        LevelData {
+            version: LEVEL_FORMAT_VERSION,
+            name: "Sample".to_string(),
+            tileset: "/tiles/sample.png".to_string(),
+            palette: Some("/tiles/sample_palette.json".to_string()),
+            masks: vec![MaskData {
+                heights: [8; TILE_SIZE],
+                angle: Some(45.0),
+            }],
+            tiles: vec![
+                TileData {
+                    collision_a: Some(0),
+                    collision_b: None,
+                    animation: Vec::new(),
+                },
+                TileData {
+                    collision_a: None,
+                    collision_b: Some(0),
+                    animation: vec![
+                        TileFrameData {
+                            tile: 1,
+                            duration: 8,
+                        },
+                        TileFrameData {
+                            tile: 2,
+                            duration: 4,
+                        },
+                    ],
+                },
+            ],
+            chunks: vec![ChunkData { tiles }],
+            foreground: LayoutData {
+                width: 2,
+                height: 1,
+                chunks: vec![Some(0), None],
+            },
+            background: LayoutData {
+                width: 1,
+                height: 1,
+                chunks: vec![Some(0)],
+            },
+            objects: vec![
+                ObjectData {
+                    x: 64.0,
+                    y: 96.0,
+                    kind: ObjectKindData::Spikes {
+                        direction: SpikeDirection::Up,
+                        width: 32.0,
+                        height: 16.0,
+                    },
+                },
+                ObjectData {
+                    x: 160.0,
+                    y: 96.0,
+                    kind: ObjectKindData::Checkpoint { index: 1 },
+                },
+                ObjectData {
+                    x: 240.0,
+                    y: 96.0,
+                    kind: ObjectKindData::Signpost,
+                },
+            ],
+            start: [32.0, 64.0],
+            camera_bounds: BoundsData {
+                min: [0.0, 0.0],
+                max: [256.0, 128.0],
+            },
+            camera_regions: vec![CameraRegionData {
+                area: BoundsData {
+                    min: [128.0, 0.0],
+                    max: [256.0, 128.0],
+                },
+                bounds: BoundsData {
+                    min: [128.0, 0.0],
+                    max: [256.0, 0.0],
+                },
+            }],
+        }
+    }
+
+    /// Validates a level, expecting an error mentioning a message.
+    fn assert_invalid(level: &LevelData, message: &str) {
+        match level.validate("test") {
+            Err(GameError::ResourceLoadError(error)) => assert!(
+                error.contains(message),
+                "expected {:?} in {:?}",
+                message,
+                error
+            ),
+            result => panic!("expected a load error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn sample_level_is_valid() {
+        assert!(sample_level().validate("test").is_ok());
+    }
+
+    #[test]
+    fn write_then_read_round_trips() {
+        let level = sample_level();
+        let mut buffer = Vec::new();
+        level.write(&mut buffer).unwrap();
+        let read = LevelData::read("test", buffer.as_slice()).unwrap();
+        assert_eq!(read, level);
+    }
+
+    #[test]
+    fn upgrades_version_1_tiles() {
+        let mut level = sample_level();
+        level.chunks[0].tiles[1] = Some(TileRef::new(1));
+        let mut value = serde_json::to_value(&level).unwrap();
+        value["version"] = Value::from(1);
+        let tiles = &mut value["chunks"][0]["tiles"];
+        tiles[0] = Value::from(0);
+        tiles[1] = Value::from(1);
+
+        let read = LevelData::read("test", value.to_string().as_bytes()).unwrap();
+        assert_eq!(read, level);
+    }
+
+    #[test]
+    fn renumbers_checkpoints_by_position() {
+        let mut level = sample_level();
+        level.objects.push(ObjectData {
+            x: 120.0,
+            y: 96.0,
+            kind: ObjectKindData::Checkpoint { index: 7 },
+        });
+        level.renumber_checkpoints();
+        let indices: Vec<(f32, u32)> = level
+            .objects
+            .iter()
+            .filter_map(|object| match object.kind {
+                ObjectKindData::Checkpoint { index } => Some((object.x, index)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(indices, vec![(160.0, 2), (120.0, 1)]);
+    }
+
+    #[test]
+    fn rejects_unsupported_version() {
+        let mut level = sample_level();
+        level.version = LEVEL_FORMAT_VERSION + 1;
+        assert_invalid(&level, "unsupported format version");
+    }
+
+    #[test]
+    fn rejects_mask_above_tile_size() {
+        let mut level = sample_level();
+        level.masks[0].heights[3] = TILE_SIZE as u8 + 1;
+        assert_invalid(&level, "mask 0 has height");
+    }
+
+    #[test]
+    fn rejects_missing_mask() {
+        let mut level = sample_level();
+        level.tiles[1].collision_b = Some(1);
+        assert_invalid(&level, "tile 1 refers to missing mask 1 on layer B");
+    }
+
+    #[test]
+    fn rejects_missing_tile() {
+        let mut level = sample_level();
+        level.chunks[0].tiles[5] = Some(TileRef {
+            tile: 2,
+            ..TileRef::default()
+        });
+        assert_invalid(&level, "chunk 0 refers to missing tile 2");
+    }
+
+    #[test]
+    fn rejects_missing_chunk() {
+        let mut level = sample_level();
+        level.foreground.chunks[1] = Some(1);
+        assert_invalid(&level, "foreground layout refers to missing chunk 1");
+    }
+
+    #[test]
+    fn rejects_wrong_chunk_size() {
+        let mut level = sample_level();
+        level.chunks[0].tiles.pop();
+        assert_invalid(&level, "chunk 0 has 63 tiles");
+    }
+
+    #[test]
+    fn rejects_wrong_layout_size() {
+        let mut level = sample_level();
+        level.background.width = 2;
+        assert_invalid(&level, "background layout has 1 chunks (expected 2x1)");
+    }
+}
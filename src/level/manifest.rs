@@ -0,0 +1,174 @@
+use crate::objects::animation::RotationMode;
+use crate::objects::player::PlayerConstants;
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+
+/// Path to the manifest listing the zones and acts of the game.
+pub const MANIFEST_PATH: &str = "/levels/manifest.json";
+
+/// Describes the physics constants players use on an act.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhysicsProfile {
+    /// Regular constants, as in Sonic 2 and Sonic 3.
+    #[default]
+    Default,
+    /// Constants reproducing Sonic 1 quirks.
+    Sonic1,
+    /// Constants for Knuckles, who jumps lower.
+    Knuckles,
+}
+
+impl PhysicsProfile {
+    /// Gets the player constants for this profile.
+    pub fn constants(&self) -> PlayerConstants {
+        match self {
+            PhysicsProfile::Default => PlayerConstants::default(),
+            PhysicsProfile::Sonic1 => PlayerConstants::default_sonic1(),
+            PhysicsProfile::Knuckles => PlayerConstants::default_knuckles(),
+        }
+    }
+}
+
+fn default_water_color() -> [f32; 4] {
+    [0.0, 0.2, 0.8, 0.35]
+}
+
+/// Describes the water of an act.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WaterData {
+    /// Vertical position of the water surface, in pixels.
+    pub height: f32,
+    /// Tint drawn over everything below the surface, as RGBA.
+    #[serde(default = "default_water_color")]
+    pub color: [f32; 4],
+}
+
+/// Describes the state players carry into the next act.
+///
+/// Rings and shields are always carried. Carrying the position makes
+/// the transition seamless: players are not sent to the start of the
+/// next act, and keep moving from where they were.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CarryOverData {
+    /// Whether players keep their position and speed.
+    #[serde(default)]
+    pub position: bool,
+    /// Offset added to the position of players, so that it matches
+    /// the coordinates of the next act.
+    #[serde(default)]
+    pub offset: [f32; 2],
+}
+
+/// Describes a single act of a zone.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActData {
+    /// Path to the level file, relative to the resources directory.
+    pub level: String,
+    /// Path to the background file, relative to the resources directory.
+    pub background: String,
+    /// Path to the music played on the act, if any.
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Physics constants used by players.
+    #[serde(default)]
+    pub physics: PhysicsProfile,
+    /// Water of the act, if any.
+    #[serde(default)]
+    pub water: Option<WaterData>,
+    /// State carried into the next act after clearing this one. If
+    /// not present, the next act starts from scratch.
+    #[serde(default)]
+    pub carry_over: Option<CarryOverData>,
+}
+
+/// Describes a zone, as a sequence of acts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ZoneData {
+    pub name: String,
+    pub acts: Vec<ActData>,
+}
+
+/// Refers to an act on a manifest.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActId {
+    /// Index of the zone.
+    pub zone: usize,
+    /// Index of the act on its zone.
+    pub act: usize,
+}
+
+/// Lists the zones of the game and their acts, in the order they
+/// are played.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ManifestData {
+    pub zones: Vec<ZoneData>,
+    /// How player sprites rotate to follow the ground angle.
+    #[serde(default)]
+    pub rotation: RotationMode,
+    /// Whether the HUD timer shows centiseconds.
+    #[serde(default)]
+    pub centiseconds: bool,
+}
+
+impl ManifestData {
+    /// Loads a manifest from a JSON file on the resources directory.
+    pub fn load(context: &mut Context, path: &str) -> GameResult<Self> {
+        let error = |message: String| {
+            GameError::ResourceLoadError(format!("Manifest {}: {}", path, message))
+        };
+        let file = filesystem::open(context, path)?;
+        let data: Self = serde_json::from_reader(file).map_err(|e| error(e.to_string()))?;
+        if data.zones.is_empty() {
+            return Err(error("no zones".to_string()));
+        }
+        if let Some(zone) = data.zones.iter().find(|zone| zone.acts.is_empty()) {
+            return Err(error(format!("zone {} has no acts", zone.name)));
+        }
+        Ok(data)
+    }
+
+    /// Gets the zone and act data referred to by an act identifier.
+    pub fn get(&self, id: ActId) -> Option<(&ZoneData, &ActData)> {
+        let zone = self.zones.get(id.zone)?;
+        Some((zone, zone.acts.get(id.act)?))
+    }
+
+    /// Gets the act which precedes another, if any. The first act of
+    /// a zone is preceded by the last act of the previous zone.
+    pub fn previous(&self, id: ActId) -> Option<ActId> {
+        if id.act > 0 {
+            Some(ActId {
+                act: id.act - 1,
+                ..id
+            })
+        } else if id.zone > 0 {
+            let zone = self.zones.get(id.zone - 1)?;
+            Some(ActId {
+                zone: id.zone - 1,
+                act: zone.acts.len().saturating_sub(1),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the act which follows another, if any. The last act of a
+    /// zone is followed by the first act of the next zone.
+    pub fn next(&self, id: ActId) -> Option<ActId> {
+        let zone = self.zones.get(id.zone)?;
+        if id.act + 1 < zone.acts.len() {
+            Some(ActId {
+                act: id.act + 1,
+                ..id
+            })
+        } else if id.zone + 1 < self.zones.len() {
+            Some(ActId {
+                zone: id.zone + 1,
+                act: 0,
+            })
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod background;
+pub mod collision;
+pub mod format;
+pub mod manifest;
+pub mod tiled;
+pub mod tilemap;
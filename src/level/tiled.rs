@@ -0,0 +1,1113 @@
+use super::collision::TILE_SIZE;
+use super::format::*;
+use crate::paths::resolve;
+use ggez::{GameError, GameResult};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::convert::TryFrom;
+
+/// Tiled stores flip flags on the three highest bits of a tile id.
+const GID_MASK: u32 = 0x1fff_ffff;
+const FLIP_X: u32 = 0x8000_0000;
+const FLIP_Y: u32 = 0x4000_0000;
+const FLIP_DIAGONAL: u32 = 0x2000_0000;
+
+/// Describes the file formats of Tiled maps and tilesets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TiledFormat {
+    /// XML format, used by .tmx maps and .tsx tilesets.
+    Xml,
+    /// JSON format, used by .tmj maps and .tsj tilesets.
+    Json,
+}
+
+impl TiledFormat {
+    /// Determines the format of a Tiled file from its extension.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = path.rsplit('.').next()?.to_lowercase();
+        match extension.as_str() {
+            "tmx" | "tsx" => Some(TiledFormat::Xml),
+            "tmj" | "tsj" => Some(TiledFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A tile from a Tiled tileset, and its custom properties.
+#[derive(Debug, Default)]
+struct TiledTile {
+    id: u32,
+    properties: Map<String, Value>,
+    /// Animation frames, as tile ids and durations in milliseconds.
+    animation: Vec<(u32, u32)>,
+}
+
+/// A Tiled tileset.
+#[derive(Debug, Default)]
+struct TiledTileset {
+    first_gid: u32,
+    source: Option<String>,
+    image: Option<String>,
+    tile_count: u32,
+    tiles: Vec<TiledTile>,
+}
+
+/// An object from a Tiled object layer.
+#[derive(Debug, Default)]
+struct TiledObject {
+    id: u32,
+    kind: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    properties: Map<String, Value>,
+}
+
+/// A Tiled layer. Group layers are flattened when parsing.
+#[derive(Debug)]
+enum TiledLayer {
+    Tiles {
+        name: String,
+        width: usize,
+        height: usize,
+        data: Vec<u32>,
+        properties: Map<String, Value>,
+    },
+    Objects {
+        objects: Vec<TiledObject>,
+    },
+}
+
+/// Format-independent representation of a Tiled map.
+#[derive(Debug, Default)]
+struct TiledMap {
+    width: usize,
+    height: usize,
+    tile_width: usize,
+    tile_height: usize,
+    properties: Map<String, Value>,
+    tilesets: Vec<TiledTileset>,
+    layers: Vec<TiledLayer>,
+}
+
+fn tiled_error(name: &str, message: String) -> GameError {
+    GameError::ResourceLoadError(format!("Tiled map {}: {}", name, message))
+}
+
+/// Imports a Tiled map in .tmx or .tmj format as level data.
+///
+/// External tilesets and the map itself are read through a function
+/// which takes a path and returns the contents of the file, so that
+/// the importer can be used both with the game filesystem and with
+/// the regular filesystem.
+///
+/// The conversion follows these rules:
+/// - Tiles must be 16x16, and only a single tileset may be used.
+///   Tiles may be flipped, but not rotated.
+/// - Tile layers named "foreground" and "background" (or with a
+///   `plane` property holding one of these) become the respective
+///   layouts. Otherwise, the first tile layer is the foreground and
+///   the second one is the background.
+/// - Tile properties `heights` and `angle` define the height mask of
+///   the tile, where `heights` is a list of 16 column heights (or a
+///   single height for all columns) and `angle` is given in degrees.
+///   `heights_b` and `angle_b` override them for collision layer B.
+/// - Tiles with a `priority` property of `high`, or placed on a tile
+///   layer with such a property, are drawn in front of low priority
+///   sprites.
+/// - Tile animations are kept, with their frame durations rounded to
+///   game frames at 60 frames per second.
+/// - Objects use their type (or class) as object kind, and their
+///   custom properties as parameters. An object of kind `start`
+///   marks the start position, and objects of kind `camera_region`
+///   become camera regions, with their camera bounds given by the
+///   `min_x`, `min_y`, `max_x` and `max_y` properties (defaulting
+///   to the object area).
+/// - Map properties `name`, `tileset` and `camera_min_x`,
+///   `camera_min_y`, `camera_max_x`, `camera_max_y` override the
+///   level name, tileset image and camera bounds. A `palette` map
+///   property sets the path to the palettes of the tileset.
+pub fn import<F>(path: &str, mut read: F) -> GameResult<LevelData>
+where
+    F: FnMut(&str) -> GameResult<String>,
+{
+    let format = TiledFormat::from_path(path)
+        .ok_or_else(|| tiled_error(path, "unknown file extension".to_string()))?;
+    let contents = read(path)?;
+    let mut map = match format {
+        TiledFormat::Xml => xml::parse_map(path, &contents)?,
+        TiledFormat::Json => json::parse_map(path, &contents)?,
+    };
+
+    // Load external tilesets
+    for tileset in map.tilesets.iter_mut() {
+        if let Some(source) = tileset.source.take() {
+            let source = resolve(path, &source);
+            let contents = read(&source)?;
+            let mut external = match TiledFormat::from_path(&source) {
+                Some(TiledFormat::Xml) => xml::parse_tileset(&source, &contents)?,
+                Some(TiledFormat::Json) => json::parse_tileset(&source, &contents)?,
+                None => {
+                    return Err(tiled_error(
+                        path,
+                        format!("unknown tileset format for {}", source),
+                    ))
+                }
+            };
+            external.first_gid = tileset.first_gid;
+            external.image = external.image.map(|image| resolve(&source, &image));
+            *tileset = external;
+        } else {
+            tileset.image = tileset.image.take().map(|image| resolve(path, &image));
+        }
+    }
+
+    convert(path, map)
+}
+
+/// Parses the heights of a height mask from a tile property.
+fn parse_heights(value: &Value) -> Result<[u8; TILE_SIZE], String> {
+    let numbers: Vec<u8> = match value {
+        Value::Number(number) => vec![number
+            .as_u64()
+            .and_then(|n| u8::try_from(n).ok())
+            .ok_or_else(|| format!("bad height {}", number))?],
+        Value::String(text) => text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                part.parse::<u8>()
+                    .map_err(|e| format!("bad height {}: {}", part, e))
+            })
+            .collect::<Result<_, _>>()?,
+        _ => return Err(format!("heights must be a list of numbers, got {}", value)),
+    };
+    if let Some(height) = numbers.iter().find(|&&h| h as usize > TILE_SIZE) {
+        return Err(format!("height {} is above {}", height, TILE_SIZE));
+    }
+    match numbers.len() {
+        1 => Ok([numbers[0]; TILE_SIZE]),
+        TILE_SIZE => {
+            let mut heights = [0; TILE_SIZE];
+            heights.copy_from_slice(&numbers);
+            Ok(heights)
+        }
+        n => Err(format!("expected 1 or {} heights, got {}", TILE_SIZE, n)),
+    }
+}
+
+fn property_f32(properties: &Map<String, Value>, name: &str) -> Option<f32> {
+    match properties.get(name)? {
+        Value::Number(number) => number.as_f64().map(|n| n as f32),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+fn property_str<'a>(properties: &'a Map<String, Value>, name: &str) -> Option<&'a str> {
+    properties.get(name).and_then(Value::as_str)
+}
+
+/// Finds or adds a height mask, returning its index.
+fn intern_mask(masks: &mut Vec<MaskData>, mask: MaskData) -> u16 {
+    match masks.iter().position(|other| *other == mask) {
+        Some(index) => index as u16,
+        None => {
+            masks.push(mask);
+            (masks.len() - 1) as u16
+        }
+    }
+}
+
+/// Builds a chunk layout out of a grid of tiles, adding new chunks
+/// to a list of chunks and reusing identical ones.
+fn build_layout(
+    chunks: &mut Vec<ChunkData>,
+    tiles: &[Option<TileRef>],
+    width: usize,
+    height: usize,
+) -> LayoutData {
+    let chunks_wide = width.div_ceil(CHUNK_TILES);
+    let chunks_high = height.div_ceil(CHUNK_TILES);
+    let mut layout = LayoutData {
+        width: chunks_wide,
+        height: chunks_high,
+        chunks: Vec::with_capacity(chunks_wide * chunks_high),
+    };
+
+    for chunk_y in 0..chunks_high {
+        for chunk_x in 0..chunks_wide {
+            let mut chunk = ChunkData {
+                tiles: Vec::with_capacity(CHUNK_TILES * CHUNK_TILES),
+            };
+            for y in 0..CHUNK_TILES {
+                for x in 0..CHUNK_TILES {
+                    let (x, y) = (chunk_x * CHUNK_TILES + x, chunk_y * CHUNK_TILES + y);
+                    let tile = if (x < width) && (y < height) {
+                        tiles[y * width + x]
+                    } else {
+                        None
+                    };
+                    chunk.tiles.push(tile);
+                }
+            }
+
+            if chunk.tiles.iter().all(Option::is_none) {
+                layout.chunks.push(None);
+                continue;
+            }
+            let index = match chunks.iter().position(|other| *other == chunk) {
+                Some(index) => index,
+                None => {
+                    chunks.push(chunk);
+                    chunks.len() - 1
+                }
+            };
+            layout.chunks.push(Some(index as u16));
+        }
+    }
+    layout
+}
+
+/// Converts a parsed Tiled map into level data.
+fn convert(name: &str, map: TiledMap) -> GameResult<LevelData> {
+    if (map.tile_width != TILE_SIZE) || (map.tile_height != TILE_SIZE) {
+        return Err(tiled_error(
+            name,
+            format!(
+                "tiles must be {}x{}, found {}x{}",
+                TILE_SIZE, TILE_SIZE, map.tile_width, map.tile_height
+            ),
+        ));
+    }
+    if map.tilesets.len() > 1 {
+        return Err(tiled_error(
+            name,
+            format!(
+                "only one tileset is supported, found {}",
+                map.tilesets.len()
+            ),
+        ));
+    }
+    let tileset = map.tilesets.into_iter().next().unwrap_or_default();
+
+    // Tiles and height masks
+    let mut masks = Vec::new();
+    let mut tiles = vec![TileData::default(); tileset.tile_count as usize];
+    for tile in tileset.tiles.iter() {
+        let error = |message| tiled_error(name, format!("tile {}: {}", tile.id, message));
+        let data = tiles
+            .get_mut(tile.id as usize)
+            .ok_or_else(|| error("outside of the tileset".to_string()))?;
+
+        if let Some(heights) = tile.properties.get("heights") {
+            let heights = parse_heights(heights).map_err(error)?;
+            let angle = property_f32(&tile.properties, "angle");
+            data.collision_a = Some(intern_mask(&mut masks, MaskData { heights, angle }));
+            data.collision_b = data.collision_a;
+        }
+        if let Some(heights) = tile.properties.get("heights_b") {
+            let heights = parse_heights(heights).map_err(error)?;
+            let angle = property_f32(&tile.properties, "angle_b");
+            data.collision_b = Some(intern_mask(&mut masks, MaskData { heights, angle }));
+        }
+
+        data.animation = tile
+            .animation
+            .iter()
+            .map(|&(tile, duration)| TileFrameData {
+                tile: tile as u16,
+                duration: ((duration * 60 + 500) / 1000).max(1),
+            })
+            .collect();
+    }
+
+    // Tile layers
+    let mut chunks = Vec::new();
+    let high_tiles: Vec<u32> = tileset
+        .tiles
+        .iter()
+        .filter(|tile| property_str(&tile.properties, "priority") == Some("high"))
+        .map(|tile| tile.id)
+        .collect();
+
+    let mut planes: [Option<LayoutData>; 2] = [None, None];
+    let mut objects = Vec::new();
+    let mut start = None;
+    let mut camera_regions = Vec::new();
+    for layer in map.layers.iter() {
+        match layer {
+            TiledLayer::Tiles {
+                name: layer_name,
+                width,
+                height,
+                data,
+                properties,
+            } => {
+                let error =
+                    |message| tiled_error(name, format!("layer {}: {}", layer_name, message));
+                if data.len() != width * height {
+                    return Err(error(format!(
+                        "has {} tiles (expected {}x{})",
+                        data.len(),
+                        width,
+                        height
+                    )));
+                }
+                let high_layer = property_str(properties, "priority") == Some("high");
+                let grid = data
+                    .iter()
+                    .map(|&gid| match gid & GID_MASK {
+                        0 => Ok(None),
+                        _ if gid & FLIP_DIAGONAL != 0 => Err(error(
+                            "has rotated tiles, which are not supported".to_string(),
+                        )),
+                        id if (id >= tileset.first_gid)
+                            && (id - tileset.first_gid < tileset.tile_count) =>
+                        {
+                            let tile = id - tileset.first_gid;
+                            Ok(Some(TileRef {
+                                tile: tile as u16,
+                                flip_x: gid & FLIP_X != 0,
+                                flip_y: gid & FLIP_Y != 0,
+                                high_priority: high_layer || high_tiles.contains(&tile),
+                            }))
+                        }
+                        id => Err(error(format!("refers to unknown tile {}", id))),
+                    })
+                    .collect::<GameResult<Vec<_>>>()?;
+
+                let plane = property_str(properties, "plane")
+                    .unwrap_or(layer_name)
+                    .to_lowercase();
+                let index = match plane.as_str() {
+                    "foreground" => 0,
+                    "background" => 1,
+                    _ => match planes.iter().position(Option::is_none) {
+                        Some(index) => index,
+                        None => return Err(error("has no plane left to fill".to_string())),
+                    },
+                };
+                if planes[index].is_some() {
+                    return Err(error(format!("{} plane is already filled", plane)));
+                }
+                planes[index] = Some(build_layout(&mut chunks, &grid, *width, *height));
+            }
+            TiledLayer::Objects { objects: layer } => {
+                for object in layer.iter() {
+                    let center = (
+                        object.x + object.width / 2.0,
+                        object.y + object.height / 2.0,
+                    );
+                    if object.kind == "start" {
+                        start = Some([center.0, center.1]);
+                        continue;
+                    }
+                    if object.kind == "camera_region" {
+                        let area = BoundsData {
+                            min: [object.x, object.y],
+                            max: [object.x + object.width, object.y + object.height],
+                        };
+                        let bound = |property, default| {
+                            property_f32(&object.properties, property).unwrap_or(default)
+                        };
+                        camera_regions.push(CameraRegionData {
+                            area,
+                            bounds: BoundsData {
+                                min: [bound("min_x", area.min[0]), bound("min_y", area.min[1])],
+                                max: [bound("max_x", area.max[0]), bound("max_y", area.max[1])],
+                            },
+                        });
+                        continue;
+                    }
+
+                    let mut fields = object.properties.clone();
+                    fields.insert("kind".to_string(), Value::from(object.kind.clone()));
+                    fields.insert("x".to_string(), Value::from(center.0));
+                    fields.insert("y".to_string(), Value::from(center.1));
+                    fields
+                        .entry("width")
+                        .or_insert_with(|| Value::from(object.width));
+                    fields
+                        .entry("height")
+                        .or_insert_with(|| Value::from(object.height));
+                    let vertical = property_str(&fields, "orientation") != Some("horizontal");
+                    fields.entry("length").or_insert_with(|| {
+                        Value::from(if vertical {
+                            object.height
+                        } else {
+                            object.width
+                        })
+                    });
+
+                    let data = ObjectData::deserialize(Value::Object(fields)).map_err(|e| {
+                        tiled_error(
+                            name,
+                            format!("object {} ({}): {}", object.id, object.kind, e),
+                        )
+                    })?;
+                    objects.push(data);
+                }
+            }
+        }
+    }
+
+    let [foreground, background] = planes;
+    let foreground =
+        foreground.ok_or_else(|| tiled_error(name, "map has no tile layers".to_string()))?;
+    let start =
+        start.ok_or_else(|| tiled_error(name, "map has no object of kind start".to_string()))?;
+
+    let size = [
+        (map.width * TILE_SIZE) as f32,
+        (map.height * TILE_SIZE) as f32,
+    ];
+    let camera_bounds = BoundsData {
+        min: [
+            property_f32(&map.properties, "camera_min_x").unwrap_or(0.0),
+            property_f32(&map.properties, "camera_min_y").unwrap_or(0.0),
+        ],
+        max: [
+            property_f32(&map.properties, "camera_max_x").unwrap_or(size[0]),
+            property_f32(&map.properties, "camera_max_y").unwrap_or(size[1]),
+        ],
+    };
+
+    let level_name = property_str(&map.properties, "name")
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            let file = name.rsplit('/').next().unwrap_or(name);
+            file.split('.').next().unwrap_or(file).to_string()
+        });
+    let tileset = property_str(&map.properties, "tileset")
+        .map(str::to_string)
+        .or(tileset.image)
+        .unwrap_or_default();
+
+    let level = LevelData {
+        version: LEVEL_FORMAT_VERSION,
+        name: level_name,
+        tileset,
+        palette: property_str(&map.properties, "palette").map(str::to_string),
+        masks,
+        tiles,
+        chunks,
+        foreground,
+        background: background.unwrap_or_default(),
+        objects,
+        start,
+        camera_bounds,
+        camera_regions,
+    };
+    level.validate(name)?;
+    Ok(level)
+}
+
+/// Parsing of maps and tilesets in XML format.
+mod xml {
+    use super::*;
+    use roxmltree::{Document, Node};
+
+    fn attribute<T: std::str::FromStr>(name: &str, node: Node, key: &str) -> GameResult<T> {
+        let value = node.attribute(key).ok_or_else(|| {
+            tiled_error(
+                name,
+                format!("<{}> is missing attribute {}", node.tag_name().name(), key),
+            )
+        })?;
+        value.parse().map_err(|_| {
+            tiled_error(
+                name,
+                format!(
+                    "<{}> has invalid attribute {}=\"{}\"",
+                    node.tag_name().name(),
+                    key,
+                    value
+                ),
+            )
+        })
+    }
+
+    fn optional<T: std::str::FromStr + Default>(
+        name: &str,
+        node: Node,
+        key: &str,
+    ) -> GameResult<T> {
+        if node.attribute(key).is_some() {
+            attribute(name, node, key)
+        } else {
+            Ok(T::default())
+        }
+    }
+
+    fn children<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+        tag: &'a str,
+    ) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+        node.children()
+            .filter(move |child| child.is_element() && child.tag_name().name() == tag)
+    }
+
+    fn properties(node: Node) -> Map<String, Value> {
+        let mut map = Map::new();
+        for property in children(node, "properties").flat_map(|n| children(n, "property")) {
+            let key = property.attribute("name").unwrap_or_default().to_string();
+            let text = property
+                .attribute("value")
+                .or_else(|| property.text())
+                .unwrap_or_default();
+            let value = match property.attribute("type").unwrap_or("string") {
+                "int" => text.parse::<i64>().map(Value::from).unwrap_or(Value::Null),
+                "float" => text
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null),
+                "bool" => Value::Bool(text == "true"),
+                _ => Value::String(text.to_string()),
+            };
+            map.insert(key, value);
+        }
+        map
+    }
+
+    fn parse_document<'input>(name: &str, contents: &'input str) -> GameResult<Document<'input>> {
+        Document::parse(contents).map_err(|e| tiled_error(name, format!("invalid XML: {}", e)))
+    }
+
+    fn tileset(name: &str, node: Node) -> GameResult<TiledTileset> {
+        let mut tiles = Vec::new();
+        for tile in children(node, "tile") {
+            let mut animation = Vec::new();
+            for frame in children(tile, "animation").flat_map(|n| children(n, "frame")) {
+                animation.push((
+                    attribute(name, frame, "tileid")?,
+                    attribute(name, frame, "duration")?,
+                ));
+            }
+            tiles.push(TiledTile {
+                id: attribute(name, tile, "id")?,
+                properties: properties(tile),
+                animation,
+            });
+        }
+        Ok(TiledTileset {
+            first_gid: optional(name, node, "firstgid")?,
+            source: node.attribute("source").map(str::to_string),
+            image: children(node, "image")
+                .next()
+                .and_then(|image| image.attribute("source"))
+                .map(str::to_string),
+            tile_count: optional(name, node, "tilecount")?,
+            tiles,
+        })
+    }
+
+    fn layers(name: &str, node: Node, layers: &mut Vec<TiledLayer>) -> GameResult {
+        for child in node.children().filter(Node::is_element) {
+            match child.tag_name().name() {
+                "layer" => {
+                    let data = children(child, "data")
+                        .next()
+                        .ok_or_else(|| tiled_error(name, "tile layer has no data".to_string()))?;
+                    let tiles = match data.attribute("encoding") {
+                        Some("csv") => data
+                            .text()
+                            .unwrap_or_default()
+                            .split(',')
+                            .map(|gid| gid.trim().parse::<u32>())
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(|e| tiled_error(name, format!("invalid tile data: {}", e)))?,
+                        None => children(data, "tile")
+                            .map(|tile| optional(name, tile, "gid"))
+                            .collect::<GameResult<Vec<_>>>()?,
+                        Some(encoding) => {
+                            return Err(tiled_error(
+                                name,
+                                format!(
+                                    "unsupported layer encoding {}, save the map using CSV",
+                                    encoding
+                                ),
+                            ))
+                        }
+                    };
+                    layers.push(TiledLayer::Tiles {
+                        name: child.attribute("name").unwrap_or_default().to_string(),
+                        width: attribute(name, child, "width")?,
+                        height: attribute(name, child, "height")?,
+                        data: tiles,
+                        properties: properties(child),
+                    });
+                }
+                "objectgroup" => {
+                    let mut objects = Vec::new();
+                    for object in children(child, "object") {
+                        objects.push(TiledObject {
+                            id: optional(name, object, "id")?,
+                            kind: object
+                                .attribute("type")
+                                .or_else(|| object.attribute("class"))
+                                .unwrap_or_default()
+                                .to_string(),
+                            x: attribute(name, object, "x")?,
+                            y: attribute(name, object, "y")?,
+                            width: optional(name, object, "width")?,
+                            height: optional(name, object, "height")?,
+                            properties: properties(object),
+                        });
+                    }
+                    layers.push(TiledLayer::Objects { objects });
+                }
+                "group" => self::layers(name, child, layers)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_map(name: &str, contents: &str) -> GameResult<TiledMap> {
+        let document = parse_document(name, contents)?;
+        let root = document.root_element();
+        if root.attribute("infinite") == Some("1") {
+            return Err(tiled_error(
+                name,
+                "infinite maps are not supported".to_string(),
+            ));
+        }
+
+        let mut map = TiledMap {
+            width: attribute(name, root, "width")?,
+            height: attribute(name, root, "height")?,
+            tile_width: attribute(name, root, "tilewidth")?,
+            tile_height: attribute(name, root, "tileheight")?,
+            properties: properties(root),
+            ..TiledMap::default()
+        };
+        for node in children(root, "tileset") {
+            map.tilesets.push(tileset(name, node)?);
+        }
+        layers(name, root, &mut map.layers)?;
+        Ok(map)
+    }
+
+    pub fn parse_tileset(name: &str, contents: &str) -> GameResult<TiledTileset> {
+        let document = parse_document(name, contents)?;
+        tileset(name, document.root_element())
+    }
+}
+
+/// Parsing of maps and tilesets in JSON format.
+mod json {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct JsonProperty {
+        name: String,
+        value: Value,
+    }
+
+    #[derive(Deserialize)]
+    struct JsonFrame {
+        tileid: u32,
+        duration: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct JsonTile {
+        id: u32,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+        #[serde(default)]
+        animation: Vec<JsonFrame>,
+    }
+
+    #[derive(Deserialize)]
+    struct JsonTileset {
+        #[serde(default)]
+        firstgid: u32,
+        source: Option<String>,
+        image: Option<String>,
+        #[serde(default)]
+        tilecount: u32,
+        #[serde(default)]
+        tiles: Vec<JsonTile>,
+    }
+
+    #[derive(Deserialize)]
+    struct JsonObject {
+        #[serde(default)]
+        id: u32,
+        #[serde(default, rename = "type")]
+        kind: String,
+        #[serde(default)]
+        class: String,
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        width: f32,
+        #[serde(default)]
+        height: f32,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(tag = "type", rename_all = "lowercase")]
+    enum JsonLayer {
+        TileLayer {
+            #[serde(default)]
+            name: String,
+            width: usize,
+            height: usize,
+            data: Value,
+            encoding: Option<String>,
+            #[serde(default)]
+            properties: Vec<JsonProperty>,
+        },
+        ObjectGroup {
+            objects: Vec<JsonObject>,
+        },
+        Group {
+            layers: Vec<JsonLayer>,
+        },
+        ImageLayer {},
+    }
+
+    #[derive(Deserialize)]
+    struct JsonMap {
+        width: usize,
+        height: usize,
+        tilewidth: usize,
+        tileheight: usize,
+        #[serde(default)]
+        infinite: bool,
+        #[serde(default)]
+        properties: Vec<JsonProperty>,
+        #[serde(default)]
+        tilesets: Vec<JsonTileset>,
+        layers: Vec<JsonLayer>,
+    }
+
+    fn properties(properties: Vec<JsonProperty>) -> Map<String, Value> {
+        properties.into_iter().map(|p| (p.name, p.value)).collect()
+    }
+
+    fn tileset(tileset: JsonTileset) -> TiledTileset {
+        TiledTileset {
+            first_gid: tileset.firstgid,
+            source: tileset.source,
+            image: tileset.image,
+            tile_count: tileset.tilecount,
+            tiles: tileset
+                .tiles
+                .into_iter()
+                .map(|tile| TiledTile {
+                    id: tile.id,
+                    properties: properties(tile.properties),
+                    animation: tile
+                        .animation
+                        .into_iter()
+                        .map(|frame| (frame.tileid, frame.duration))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+
+    fn layers(name: &str, source: Vec<JsonLayer>, layers: &mut Vec<TiledLayer>) -> GameResult {
+        for layer in source {
+            match layer {
+                JsonLayer::TileLayer {
+                    name: layer_name,
+                    width,
+                    height,
+                    data,
+                    encoding,
+                    properties: layer_properties,
+                } => {
+                    if let Some(encoding) = encoding.filter(|e| e != "csv") {
+                        return Err(tiled_error(
+                            name,
+                            format!(
+                                "unsupported layer encoding {}, save the map using CSV",
+                                encoding
+                            ),
+                        ));
+                    }
+                    let data = Vec::<u32>::deserialize(data)
+                        .map_err(|e| tiled_error(name, format!("invalid tile data: {}", e)))?;
+                    layers.push(TiledLayer::Tiles {
+                        name: layer_name,
+                        width,
+                        height,
+                        data,
+                        properties: properties(layer_properties),
+                    });
+                }
+                JsonLayer::ObjectGroup { objects } => {
+                    let objects = objects
+                        .into_iter()
+                        .map(|object| TiledObject {
+                            id: object.id,
+                            kind: if object.kind.is_empty() {
+                                object.class
+                            } else {
+                                object.kind
+                            },
+                            x: object.x,
+                            y: object.y,
+                            width: object.width,
+                            height: object.height,
+                            properties: properties(object.properties),
+                        })
+                        .collect();
+                    layers.push(TiledLayer::Objects { objects });
+                }
+                JsonLayer::Group { layers: children } => self::layers(name, children, layers)?,
+                JsonLayer::ImageLayer {} => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_map(name: &str, contents: &str) -> GameResult<TiledMap> {
+        let map: JsonMap = serde_json::from_str(contents)
+            .map_err(|e| tiled_error(name, format!("invalid map: {}", e)))?;
+        if map.infinite {
+            return Err(tiled_error(
+                name,
+                "infinite maps are not supported".to_string(),
+            ));
+        }
+
+        let mut result = TiledMap {
+            width: map.width,
+            height: map.height,
+            tile_width: map.tilewidth,
+            tile_height: map.tileheight,
+            properties: properties(map.properties),
+            tilesets: map.tilesets.into_iter().map(tileset).collect(),
+            layers: Vec::new(),
+        };
+        layers(name, map.layers, &mut result.layers)?;
+        Ok(result)
+    }
+
+    pub fn parse_tileset(name: &str, contents: &str) -> GameResult<TiledTileset> {
+        serde_json::from_str(contents)
+            .map(tileset)
+            .map_err(|e| tiled_error(name, format!("invalid tileset: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::hazards::SpikeDirection;
+
+    const HEIGHTS: &str = "0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15";
+
+    const TMX_MAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="2" height="1" tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" source="../tiles/test.tsx"/>
+ <layer id="1" name="foreground" width="2" height="1">
+  <data encoding="csv">2147483650,1073741827</data>
+ </layer>
+ <objectgroup id="2" name="objects">
+  <object id="1" type="start" x="0" y="0" width="32" height="16"/>
+  <object id="2" class="spikes" x="16" y="0" width="16" height="16">
+   <properties>
+    <property name="direction" value="up"/>
+   </properties>
+  </object>
+  <object id="3" type="checkpoint" x="8" y="4">
+   <properties>
+    <property name="index" type="int" value="1"/>
+   </properties>
+  </object>
+ </objectgroup>
+</map>"#;
+
+    const TSX_TILESET: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="test" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+ <image source="test.png" width="32" height="32"/>
+ <tile id="1">
+  <properties>
+   <property name="heights" type="int" value="16"/>
+   <property name="angle" type="float" value="0"/>
+  </properties>
+ </tile>
+ <tile id="2">
+  <properties>
+   <property name="heights" value="0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15"/>
+   <property name="angle" type="float" value="45"/>
+   <property name="heights_b" type="int" value="8"/>
+   <property name="angle_b" type="float" value="-45"/>
+   <property name="priority" value="high"/>
+  </properties>
+ </tile>
+</tileset>"#;
+
+    const TMJ_MAP: &str = r#"{
+        "width": 2, "height": 1, "tilewidth": 16, "tileheight": 16, "infinite": false,
+        "tilesets": [{
+            "firstgid": 1, "image": "../tiles/test.png", "tilecount": 4,
+            "tiles": [
+                {"id": 1, "properties": [
+                    {"name": "heights", "type": "int", "value": 16},
+                    {"name": "angle", "type": "float", "value": 0}
+                ]},
+                {"id": 2, "properties": [
+                    {"name": "heights", "type": "string", "value": "0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15"},
+                    {"name": "angle", "type": "float", "value": 45},
+                    {"name": "heights_b", "type": "int", "value": 8},
+                    {"name": "angle_b", "type": "float", "value": -45},
+                    {"name": "priority", "type": "string", "value": "high"}
+                ]}
+            ]
+        }],
+        "layers": [
+            {"type": "tilelayer", "name": "foreground", "width": 2, "height": 1,
+             "data": [2147483650, 1073741827]},
+            {"type": "objectgroup", "objects": [
+                {"id": 1, "type": "start", "x": 0, "y": 0, "width": 32, "height": 16},
+                {"id": 2, "class": "spikes", "x": 16, "y": 0, "width": 16, "height": 16,
+                 "properties": [{"name": "direction", "type": "string", "value": "up"}]},
+                {"id": 3, "type": "checkpoint", "x": 8, "y": 4,
+                 "properties": [{"name": "index", "type": "int", "value": 1}]}
+            ]}
+        ]
+    }"#;
+
+    /// Serves the fixtures as if they were files.
+    fn read(path: &str) -> GameResult<String> {
+        match path {
+            "/levels/test.tmx" => Ok(TMX_MAP.to_string()),
+            "/levels/test.tmj" => Ok(TMJ_MAP.to_string()),
+            "/tiles/test.tsx" => Ok(TSX_TILESET.to_string()),
+            _ => Err(tiled_error(path, "file not found".to_string())),
+        }
+    }
+
+    fn tile_at(level: &LevelData, x: usize) -> Option<TileRef> {
+        let chunk = level.foreground.get(0, 0).expect("missing chunk");
+        level.chunks[chunk as usize].tiles[x]
+    }
+
+    #[test]
+    fn imports_xml_map_with_external_tileset() {
+        let level = import("/levels/test.tmx", read).unwrap();
+        assert_eq!(level.name, "test");
+        assert_eq!(level.tileset, "/tiles/test.png");
+        assert_eq!(level.start, [16.0, 8.0]);
+        assert_eq!(level.camera_bounds.max, [32.0, 16.0]);
+    }
+
+    #[test]
+    fn imports_flip_flags() {
+        let level = import("/levels/test.tmx", read).unwrap();
+        assert_eq!(
+            tile_at(&level, 0),
+            Some(TileRef {
+                tile: 1,
+                flip_x: true,
+                flip_y: false,
+                high_priority: false,
+            })
+        );
+        assert_eq!(
+            tile_at(&level, 1),
+            Some(TileRef {
+                tile: 2,
+                flip_x: false,
+                flip_y: true,
+                high_priority: true,
+            })
+        );
+    }
+
+    #[test]
+    fn imports_tile_masks_and_angles() {
+        let level = import("/levels/test.tmx", read).unwrap();
+        let mut slope = [0; TILE_SIZE];
+        for (i, height) in slope.iter_mut().enumerate() {
+            *height = i as u8;
+        }
+        assert_eq!(
+            level.masks,
+            vec![
+                MaskData {
+                    heights: [16; TILE_SIZE],
+                    angle: Some(0.0),
+                },
+                MaskData {
+                    heights: slope,
+                    angle: Some(45.0),
+                },
+                MaskData {
+                    heights: [8; TILE_SIZE],
+                    angle: Some(-45.0),
+                },
+            ]
+        );
+        assert_eq!(level.tiles[0].collision_a, None);
+        assert_eq!(level.tiles[1].collision_a, Some(0));
+        assert_eq!(level.tiles[1].collision_b, Some(0));
+        assert_eq!(level.tiles[2].collision_a, Some(1));
+        assert_eq!(level.tiles[2].collision_b, Some(2));
+    }
+
+    #[test]
+    fn imports_object_properties() {
+        let level = import("/levels/test.tmx", read).unwrap();
+        assert_eq!(
+            level.objects,
+            vec![
+                ObjectData {
+                    x: 24.0,
+                    y: 8.0,
+                    kind: ObjectKindData::Spikes {
+                        direction: SpikeDirection::Up,
+                        width: 16.0,
+                        height: 16.0,
+                    },
+                },
+                ObjectData {
+                    x: 8.0,
+                    y: 4.0,
+                    kind: ObjectKindData::Checkpoint { index: 1 },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn json_map_matches_xml_map() {
+        let xml = import("/levels/test.tmx", read).unwrap();
+        let json = import("/levels/test.tmj", read).unwrap();
+        assert_eq!(json, xml);
+    }
+
+    #[test]
+    fn parses_heights() {
+        assert_eq!(parse_heights(&Value::from(4)), Ok([4; TILE_SIZE]));
+        assert_eq!(parse_heights(&Value::from("16")), Ok([16; TILE_SIZE]));
+        assert_eq!(parse_heights(&Value::from(HEIGHTS)).map(|h| h[15]), Ok(15));
+    }
+
+    #[test]
+    fn rejects_bad_heights() {
+        for value in [
+            Value::from(300),
+            Value::from(-1),
+            Value::from(1.5),
+            Value::from(17),
+            Value::from("17"),
+            Value::from("1,2,3"),
+        ] {
+            assert!(parse_heights(&value).is_err(), "accepted {}", value);
+        }
+    }
+}
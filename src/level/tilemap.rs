@@ -0,0 +1,218 @@
+use super::collision::TILE_SIZE;
+use super::format::{LayoutData, LevelData, TileFrameData, CHUNK_SIZE, CHUNK_TILES};
+use crate::objects::general::Priority;
+use ggez::graphics::spritebatch::SpriteBatch;
+use ggez::graphics::{self, DrawParam, FilterMode, Image, Rect};
+use ggez::{Context, GameResult};
+use glam::*;
+
+/// An animated tile, along with the length of its whole cycle.
+#[derive(Debug, Clone, PartialEq)]
+struct AnimatedTile {
+    tile: u16,
+    frames: Vec<TileFrameData>,
+    cycle: u64,
+}
+
+/// Keeps track of the graphics shown by animated tiles.
+///
+/// All animated tiles of a level share the same timer, which counts
+/// game frames rather than elapsed time, so that animations play the
+/// same way every time. Should be shared by all planes of a level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileAnimations {
+    timer: u64,
+    animated: Vec<AnimatedTile>,
+    graphics: Vec<u16>,
+}
+
+impl TileAnimations {
+    /// Creates the animation state for the tiles of a level, with
+    /// all animations on their first frame.
+    pub fn new(level: &LevelData) -> Self {
+        let animated = level
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| !tile.animation.is_empty())
+            .map(|(index, tile)| AnimatedTile {
+                tile: index as u16,
+                frames: tile.animation.clone(),
+                cycle: tile
+                    .animation
+                    .iter()
+                    .map(|frame| frame.duration as u64)
+                    .sum(),
+            })
+            .collect();
+        let mut animations = Self {
+            timer: 0,
+            animated,
+            graphics: (0..level.tiles.len() as u16).collect(),
+        };
+        animations.refresh();
+        animations
+    }
+
+    /// Advances all animations by a single frame.
+    pub fn update(&mut self) {
+        self.timer += 1;
+        self.refresh();
+    }
+
+    /// Gets the graphics currently shown by a tile.
+    pub fn graphics(&self, tile: u16) -> u16 {
+        self.graphics.get(tile as usize).copied().unwrap_or(tile)
+    }
+
+    fn refresh(&mut self) {
+        for animated in self.animated.iter() {
+            let mut time = self.timer % animated.cycle.max(1);
+            for frame in animated.frames.iter() {
+                if time < frame.duration as u64 {
+                    self.graphics[animated.tile as usize] = frame.tile;
+                    break;
+                }
+                time -= frame.duration as u64;
+            }
+        }
+    }
+}
+
+/// Renders a plane of a level, given by a chunk layout.
+///
+/// Tiles are taken from a tileset image, where tiles are arranged
+/// in a grid and numerated from 0 in the left-right, top-bottom
+/// order. Only the tiles visible on a given area of the level are
+/// queued, so the cost of drawing a plane does not depend on the
+/// size of the level.
+///
+/// Low and high priority tiles are queued separately, so that
+/// sprites can be drawn between them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TilemapRenderer {
+    texture: Image,
+    low: SpriteBatch,
+    high: SpriteBatch,
+    columns: usize,
+}
+
+impl TilemapRenderer {
+    /// Creates a new tilemap renderer from a tileset image.
+    pub fn new(mut texture: Image) -> Self {
+        texture.set_filter(FilterMode::Nearest);
+        let columns = (texture.width() as usize / TILE_SIZE).max(1);
+        Self {
+            low: SpriteBatch::new(texture.clone()),
+            high: SpriteBatch::new(texture.clone()),
+            texture,
+            columns,
+        }
+    }
+
+    /// Replaces the tileset image, such as a recolored version of it.
+    /// The new image must have the same size.
+    pub fn set_texture(&mut self, mut texture: Image) {
+        texture.set_filter(FilterMode::Nearest);
+        self.low.set_image(texture.clone());
+        self.high.set_image(texture.clone());
+        self.texture = texture;
+    }
+
+    /// Calculates the area of the tileset covered by a tile, in texels.
+    fn calculate_tile(&self, tile: u16) -> Rect {
+        let tile = tile as usize;
+        let size = glam::vec2(self.texture.width() as f32, self.texture.height() as f32);
+        let tile_texels = Vec2::splat(TILE_SIZE as f32) / size;
+        Rect::new(
+            (tile % self.columns) as f32 * tile_texels.x,
+            (tile / self.columns) as f32 * tile_texels.y,
+            tile_texels.x,
+            tile_texels.y,
+        )
+    }
+
+    /// Queues the tiles of a layout which are visible on a certain
+    /// area of the level, replacing anything queued before. Animated
+    /// tiles show their current frame.
+    pub fn queue<F>(
+        &mut self,
+        level: &LevelData,
+        layout: &LayoutData,
+        animations: &TileAnimations,
+        view: Rect,
+        transform: F,
+    ) where
+        F: Fn(Vec2) -> Vec2,
+    {
+        self.low.clear();
+        self.high.clear();
+        if (layout.width == 0) || (layout.height == 0) {
+            return;
+        }
+
+        // Range of visible tiles, clamped to the layout
+        let tile = TILE_SIZE as f32;
+        let width = layout.width * CHUNK_TILES;
+        let height = layout.height * CHUNK_TILES;
+        let first = ((view.x / tile).floor().max(0.0) as usize).min(width);
+        let last = ((view.right() / tile).ceil().max(0.0) as usize).min(width);
+        let top = ((view.y / tile).floor().max(0.0) as usize).min(height);
+        let bottom = ((view.bottom() / tile).ceil().max(0.0) as usize).min(height);
+
+        for chunk_y in (top / CHUNK_TILES)..bottom.div_ceil(CHUNK_TILES) {
+            for chunk_x in (first / CHUNK_TILES)..last.div_ceil(CHUNK_TILES) {
+                let chunk = match layout
+                    .get(chunk_x, chunk_y)
+                    .and_then(|chunk| level.chunks.get(chunk as usize))
+                {
+                    Some(chunk) => chunk,
+                    None => continue,
+                };
+                let origin =
+                    glam::vec2((chunk_x * CHUNK_SIZE) as f32, (chunk_y * CHUNK_SIZE) as f32);
+
+                // Only the part of the chunk which is visible
+                let x_range = first.saturating_sub(chunk_x * CHUNK_TILES)
+                    ..(last - chunk_x * CHUNK_TILES).min(CHUNK_TILES);
+                let y_range = top.saturating_sub(chunk_y * CHUNK_TILES)
+                    ..(bottom - chunk_y * CHUNK_TILES).min(CHUNK_TILES);
+                for y in y_range {
+                    for x in x_range.clone() {
+                        let tile_ref = match chunk.tiles[y * CHUNK_TILES + x] {
+                            Some(tile_ref) => tile_ref,
+                            None => continue,
+                        };
+                        let position = origin + glam::vec2(x as f32, y as f32) * tile;
+                        let mut destination = transform(position).round();
+                        let mut scale = Vec2::ONE;
+                        if tile_ref.flip_x {
+                            destination.x += tile;
+                            scale.x = -1.0;
+                        }
+                        if tile_ref.flip_y {
+                            destination.y += tile;
+                            scale.y = -1.0;
+                        }
+                        let src = self.calculate_tile(animations.graphics(tile_ref.tile));
+                        let batch = if tile_ref.high_priority {
+                            &mut self.high
+                        } else {
+                            &mut self.low
+                        };
+                        batch.add(DrawParam::default().src(src).scale(scale).dest(destination));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws the queued tiles of a certain priority.
+    pub fn draw(&self, context: &mut Context, priority: Priority) -> GameResult {
+        let batch = match priority {
+            Priority::Low => &self.low,
+            Priority::High => &self.high,
+        };
+        graphics::draw(context, batch, DrawParam::default())
+    }
+}
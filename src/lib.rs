@@ -1,12 +1,17 @@
+pub mod display;
+mod font;
 mod input;
+pub mod level;
 mod objects;
+mod paths;
 mod screen_systems;
 
+use display::Display;
 use ggez::event::Axis;
 use ggez::event::Button;
 use ggez::event::EventHandler;
-use ggez::event::{KeyCode, KeyMods};
-use ggez::graphics::{self, Color};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
+use ggez::graphics;
 use ggez::input::gamepad::GamepadId;
 use ggez::timer;
 use ggez::{Context, GameError, GameResult};
@@ -18,18 +23,20 @@ const DESIRED_FPS: u32 = 60;
 
 /// Represents the main game state.
 pub struct MainState {
+    display: Display,
     navigation: Navigation,
     screen_systems: ScreenSystems,
     input: Input,
 }
 
 impl MainState {
-    /// Creates a new MainState.
-    pub fn new(game_name: &'static str) -> GameResult<Self> {
+    /// Creates a new MainState, which renders through a certain display.
+    pub fn new(game_name: &'static str, display: Display) -> GameResult<Self> {
         let navigation = Navigation::default();
         let screen_systems = ScreenSystems::new(game_name);
         let input = Input::default();
         Ok(Self {
+            display,
             navigation,
             screen_systems,
             input,
@@ -39,9 +46,17 @@ impl MainState {
     /// Sets up the MainState by loading assets and setting up the initial
     /// state.
     pub fn setup(&mut self, context: &mut Context) -> GameResult {
+        self.display.setup(context)?;
         self.screen_systems.setup(context)?;
         Ok(())
     }
+
+    /// Tracks the mouse position, converted from window pixels to
+    /// virtual pixels.
+    fn set_mouse_position(&mut self, context: &Context, x: f32, y: f32) {
+        let position = self.display.to_virtual(context, glam::vec2(x, y));
+        self.input.set_mouse_position(position.x, position.y);
+    }
 }
 
 impl EventHandler<GameError> for MainState {
@@ -55,8 +70,9 @@ impl EventHandler<GameError> for MainState {
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        graphics::clear(ctx, Color::BLACK);
+        self.display.begin(ctx)?;
         self.screen_systems.draw(ctx, &self.navigation)?;
+        self.display.finish(ctx)?;
         graphics::present(ctx)
     }
 
@@ -74,6 +90,24 @@ impl EventHandler<GameError> for MainState {
         self.input.set_keyboard(keycode, false);
     }
 
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        self.set_mouse_position(ctx, x, y);
+        self.input.set_mouse_button(button, true);
+    }
+
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        self.set_mouse_position(ctx, x, y);
+        self.input.set_mouse_button(button, false);
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.set_mouse_position(ctx, x, y);
+    }
+
+    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) {
+        self.input.add_wheel(y);
+    }
+
     fn gamepad_button_down_event(&mut self, _ctx: &mut Context, btn: Button, _id: GamepadId) {
         self.input.set_gamepad(btn, true);
     }
@@ -5,12 +5,17 @@ use ggez::{
     conf::{FullscreenType, WindowMode, WindowSetup},
     event, ContextBuilder, GameResult,
 };
+use sonic_platformer::display::{Display, ScaleMode};
 use sonic_platformer::*;
 use std::env;
 use std::path::PathBuf;
 
 const GAME_NAME: &str = "sonic-platformer";
 
+/// Native resolution the game is rendered at, before scaling.
+const VIRTUAL_WIDTH: u16 = 424;
+const VIRTUAL_HEIGHT: u16 = 240;
+
 fn get_resource_dir() -> PathBuf {
     if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
         let mut path = PathBuf::from(manifest_dir);
@@ -27,7 +32,7 @@ fn main() -> GameResult {
     let window_mode = WindowMode::default()
         //.fullscreen_type(FullscreenType::Desktop)
         .dimensions(854.0, 480.0)
-        .resizable(false);
+        .resizable(true);
 
     let (mut ctx, event_loop) = ContextBuilder::new("sonic_platformer", "Lucas S. Vieira")
         .window_mode(window_mode)
@@ -35,7 +40,8 @@ fn main() -> GameResult {
         .add_resource_path(get_resource_dir())
         .build()?;
 
-    let mut main_state = MainState::new(GAME_NAME)?;
+    let display = Display::new(VIRTUAL_WIDTH, VIRTUAL_HEIGHT, ScaleMode::Integer);
+    let mut main_state = MainState::new(GAME_NAME, display)?;
     main_state.setup(&mut ctx)?;
     event::run(ctx, event_loop, main_state)
 }
@@ -0,0 +1,109 @@
+use super::animation::{Animator, SpritePose};
+use super::sprite_atlas::SpriteAtlas;
+use ggez::graphics::Color;
+use ggez::{Context, GameResult};
+use glam::*;
+use std::collections::VecDeque;
+
+/// Opacity of the newest copy of an afterimage. Older copies fade
+/// out from there.
+const AFTERIMAGE_OPACITY: f32 = 0.6;
+
+/// Refers to an afterimage component, which leaves fading copies of
+/// an animated sprite behind it.
+///
+/// Records the position and pose of the sprite on every frame, and
+/// draws copies of some of the recorded frames, so that the trail
+/// follows the path of the sprite. Meant for speed shoes, Super forms
+/// and drop dashes, and for high speeds in general.
+///
+/// Copies are drawn through a sprite atlas of their own, sharing the
+/// image of the sprite, so that they are drawn behind it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Afterimage {
+    /// Whether the trail is growing. When inactive, the trail shrinks
+    /// by a frame at a time until it disappears.
+    pub active: bool,
+    /// Amount of copies drawn.
+    pub length: usize,
+    /// Amount of frames between copies.
+    pub spacing: usize,
+    atlas: SpriteAtlas,
+    /// Recorded positions and poses, with the newest first.
+    history: VecDeque<(Vec2, SpritePose)>,
+}
+
+impl Afterimage {
+    /// Creates an inactive afterimage for a sprite, with a certain
+    /// amount of copies spaced by a certain amount of frames.
+    pub fn new(atlas: &SpriteAtlas, length: usize, spacing: usize) -> Self {
+        let mut atlas = atlas.clone();
+        atlas.clear();
+        Self {
+            active: false,
+            length,
+            spacing: spacing.max(1),
+            atlas,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Removes all copies at once, such as when the sprite is moved
+    /// somewhere else.
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.atlas.clear();
+    }
+
+    /// Records the current pose of a sprite at a certain position, and
+    /// queues the copies for drawing.
+    ///
+    /// Hidden sprites are not recorded and have no copies.
+    pub fn update<F>(
+        &mut self,
+        animator: &Animator,
+        atlas: &SpriteAtlas,
+        position: Vec2,
+        visible: bool,
+        transform: F,
+    ) -> GameResult
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        self.atlas.clear();
+        if !visible {
+            return Ok(());
+        }
+        if self.active {
+            self.history.push_front((position, animator.pose()));
+            self.history.truncate(self.length * self.spacing + 1);
+        } else {
+            self.history.pop_back();
+        }
+
+        // Follow palette changes of the sprite
+        self.atlas.set_texture(atlas.texture().clone());
+
+        // Queue from the oldest copy, so that newer ones are on top
+        for copy in (1..=self.length).rev() {
+            let (position, pose) = match self.history.get(copy * self.spacing) {
+                Some(record) => *record,
+                None => continue,
+            };
+            let opacity = AFTERIMAGE_OPACITY * (1.0 - (copy - 1) as f32 / self.length as f32);
+            self.atlas.queue_draw_tinted(
+                pose.frame,
+                transform(position),
+                pose.scale,
+                pose.rotation,
+                Color::new(1.0, 1.0, 1.0, opacity),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Draws the queued copies.
+    pub fn draw(&self, context: &mut Context) -> GameResult {
+        self.atlas.draw(context)
+    }
+}
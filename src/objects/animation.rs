@@ -3,9 +3,32 @@ use crate::objects::general::Direction;
 use crate::objects::general::Position;
 use ggez::{Context, GameError, GameResult};
 use glam::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+/// Describes how an animator rotates its sprite.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationMode {
+    /// Rotation is snapped to 45 degree steps, as on the Genesis games.
+    #[default]
+    Snapped,
+    /// Rotation follows the given angle exactly, as on Sonic Mania.
+    Smooth,
+}
+
+/// Describes how the current frame of an animator is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpritePose {
+    pub frame: u32,
+    /// Scale factor related to each axis, which is negative on the
+    /// horizontal axis when the sprite is flipped.
+    pub scale: Vec2,
+    /// Clockwise rotation around the frame pivot, in radians.
+    pub rotation: f32,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct AnimationData {
     pub frames: Vec<u32>,
@@ -18,6 +41,7 @@ pub struct AnimationData {
 /// Allows you to build an animator by adding animations to it.
 pub struct AnimatorBuilder {
     pub data: HashMap<String, AnimationData>,
+    pub rotation_mode: RotationMode,
 }
 
 impl AnimatorBuilder {
@@ -25,9 +49,16 @@ impl AnimatorBuilder {
     pub fn new() -> Self {
         Self {
             data: HashMap::new(),
+            rotation_mode: RotationMode::default(),
         }
     }
 
+    /// Sets how the animator rotates its sprite.
+    pub fn rotation_mode(&mut self, mode: RotationMode) -> &mut Self {
+        self.rotation_mode = mode;
+        self
+    }
+
     /// Adds an animation to the builder.
     pub fn add_animation(
         &mut self,
@@ -59,6 +90,7 @@ impl AnimatorBuilder {
     pub fn build(&self) -> Animator {
         Animator {
             data: self.data.clone(),
+            rotation_mode: self.rotation_mode,
             ..Animator::default()
         }
     }
@@ -78,6 +110,8 @@ pub struct Animator {
     last_update: Instant,
     pub direction: Direction,
     scale: f32,
+    rotation: f32,
+    rotation_mode: RotationMode,
     frame_duration: Duration,
     data: HashMap<String, AnimationData>,
 }
@@ -91,6 +125,8 @@ impl Default for Animator {
             last_update: Instant::now(),
             direction: Direction::Right,
             scale: 1.0,
+            rotation: 0.0,
+            rotation_mode: RotationMode::default(),
             frame_duration: Duration::from_millis(16),
             data: HashMap::new(),
         }
@@ -126,6 +162,28 @@ impl Animator {
         self.frame_duration = Duration::from_millis(duration);
     }
 
+    /// Sets the clockwise rotation of the sprite, in radians. The
+    /// rotation is snapped according to the rotation mode.
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = match self.rotation_mode {
+            RotationMode::Smooth => rotation,
+            RotationMode::Snapped => {
+                let step = std::f32::consts::FRAC_PI_4;
+                (rotation / step).round() * step
+            }
+        };
+    }
+
+    /// Gets how the current frame is drawn, as of the last update.
+    pub fn pose(&self) -> SpritePose {
+        let direction: f32 = self.direction.into();
+        SpritePose {
+            frame: self.current_frame,
+            scale: glam::vec2(direction * self.scale, self.scale),
+            rotation: self.rotation,
+        }
+    }
+
     /// Updates the animation.
     pub fn update(&mut self, atlas: &mut SpriteAtlas, hotspot: &Position) -> GameResult {
         if let Some(data) = self.data.get(&self.animation_name) {
@@ -158,13 +216,8 @@ impl Animator {
             self.current_frame = *data.frames.get(self.frame_count).unwrap_or(&0);
 
             // Queue drawing
-            let direction: f32 = self.direction.into();
-            let xscale = direction * self.scale;
-            atlas.queue_draw(
-                self.current_frame,
-                hotspot.0,
-                glam::vec2(xscale, self.scale),
-            )
+            let pose = self.pose();
+            atlas.queue_draw(pose.frame, hotspot.0, pose.scale, pose.rotation)
         } else {
             Ok(())
         }
@@ -0,0 +1,85 @@
+use super::camera::CameraBounds;
+use super::general::{centered_rect, Position};
+use super::player::PlayerState;
+use ggez::graphics::{self, Color, DrawMode, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use glam::*;
+use legion::*;
+
+/// Refers to an arena lock trigger component.
+///
+/// When a player enters the trigger area, the camera is locked into
+/// the arena bounds, as it happens before boss fights. The lock is
+/// kept until something releases it, such as the boss being defeated,
+/// the act being cleared or the player dying.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArenaLock {
+    /// Size of the trigger area, centered on the object position.
+    pub size: Vec2,
+    /// Camera bounds while locked.
+    pub bounds: CameraBounds,
+    /// Whether the lock was already triggered.
+    pub triggered: bool,
+}
+
+impl ArenaLock {
+    /// Updates all arena locks in the world.
+    ///
+    /// Returns the camera bounds of an arena, if a player entered its
+    /// trigger area on this frame.
+    pub fn update(world: &mut World) -> GameResult<Option<CameraBounds>> {
+        let players: Vec<Position> = <(&PlayerState, &Position)>::query()
+            .iter(world)
+            .filter(|(state, _)| !state.is_dead())
+            .map(|(_, position)| *position)
+            .collect();
+
+        let mut locked = None;
+        let mut query = <(&mut ArenaLock, &Position)>::query();
+        for (arena, position) in query.iter_mut(world) {
+            if arena.triggered {
+                continue;
+            }
+            let area = centered_rect(position, arena.size);
+            if players.iter().any(|player| area.contains(player.0)) {
+                arena.triggered = true;
+                locked = Some(arena.bounds);
+            }
+        }
+        Ok(locked)
+    }
+
+    /// Draws the trigger area and bounds of all arena locks in the
+    /// world, for debugging purposes.
+    pub fn debug_draw<F>(world: &World, context: &mut Context, transform: F) -> GameResult
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        let mut query = <(&ArenaLock, &Position)>::query();
+        for (arena, position) in query.iter(world) {
+            let area = centered_rect(position, arena.size);
+            let corner = transform(glam::vec2(area.x, area.y));
+            let min = transform(arena.bounds.min);
+            let max = transform(arena.bounds.max);
+            let color = if arena.triggered {
+                Color::new(1.0, 0.0, 0.0, 0.8)
+            } else {
+                Color::new(1.0, 0.0, 1.0, 0.8)
+            };
+            let mesh = MeshBuilder::new()
+                .rectangle(
+                    DrawMode::stroke(1.0),
+                    Rect::new(corner.x, corner.y, area.w, area.h),
+                    color,
+                )?
+                .rectangle(
+                    DrawMode::stroke(1.0),
+                    Rect::new(min.x, min.y, max.x - min.x, max.y - min.y),
+                    color,
+                )?
+                .build(context)?;
+            graphics::draw(context, &mesh, (Vec2::ZERO, 0.0, Color::WHITE))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use super::animation::AnimatorBuilder;
+use super::atlas_import;
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Read;
+
+fn default_loops() -> bool {
+    true
+}
+
+/// Describes a single frame of a sprite atlas.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AtlasFrameData {
+    /// Name of the frame, for reference only.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// Area of the frame on the image, as x, y, width and height,
+    /// in pixels.
+    pub rect: [f32; 4],
+    /// Point of the frame drawn at the sprite position, relative to
+    /// the top left corner of the frame. Defaults to the center.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<[f32; 2]>,
+}
+
+impl AtlasFrameData {
+    /// Gets the pivot of the frame, relative to its top left corner.
+    pub fn pivot(&self) -> [f32; 2] {
+        self.pivot
+            .unwrap_or([self.rect[2] / 2.0, self.rect[3] / 2.0])
+    }
+}
+
+/// Describes an animation of a sprite atlas, as a sequence of frames.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AtlasAnimationData {
+    pub name: String,
+    /// Indices of the frames shown by the animation, in order.
+    pub frames: Vec<u32>,
+    #[serde(default = "default_loops")]
+    pub loops: bool,
+    /// Index on the animation frames where a looping animation
+    /// restarts from.
+    #[serde(default)]
+    pub loopback_index: usize,
+    pub ms_per_frame: u64,
+}
+
+/// Describes a sprite atlas, as a sidecar file for its image.
+///
+/// Frames may have any size and be placed anywhere on the image, and
+/// are referred to by their index on the frame list.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AtlasData {
+    /// Path to the atlas image, relative to the resources directory.
+    pub image: String,
+    pub frames: Vec<AtlasFrameData>,
+    #[serde(default)]
+    pub animations: Vec<AtlasAnimationData>,
+}
+
+pub(crate) fn atlas_error(name: &str, message: String) -> GameError {
+    GameError::ResourceLoadError(format!("Sprite atlas {}: {}", name, message))
+}
+
+impl AtlasData {
+    /// Loads atlas data from a JSON file on the resources directory.
+    ///
+    /// Sprite sheets exported by Aseprite or TexturePacker are imported
+    /// on the fly, and any other file is expected to be an atlas file.
+    pub fn load(context: &mut Context, path: &str) -> GameResult<Self> {
+        let mut contents = String::new();
+        filesystem::open(context, path)?.read_to_string(&mut contents)?;
+        let value: Value = serde_json::from_str(&contents)
+            .map_err(|e| atlas_error(path, format!("invalid JSON: {}", e)))?;
+        let data = if value.get("meta").is_some() {
+            atlas_import::import(path, &contents)?
+        } else {
+            serde_json::from_value(value)
+                .map_err(|e| atlas_error(path, format!("invalid atlas file: {}", e)))?
+        };
+        data.validate(path)?;
+        Ok(data)
+    }
+
+    /// Checks atlas data for inconsistencies, such as empty frames and
+    /// animations referring to nonexistent frames.
+    pub fn validate(&self, name: &str) -> GameResult {
+        if self.frames.is_empty() {
+            return Err(atlas_error(name, "no frames".to_string()));
+        }
+        for (i, frame) in self.frames.iter().enumerate() {
+            if (frame.rect[2] <= 0.0) || (frame.rect[3] <= 0.0) {
+                return Err(atlas_error(name, format!("frame {} is empty", i)));
+            }
+        }
+        for animation in self.animations.iter() {
+            let error = |message: &str| {
+                atlas_error(name, format!("animation {} {}", animation.name, message))
+            };
+            if animation.frames.is_empty() {
+                return Err(error("has no frames"));
+            }
+            if let Some(frame) = animation
+                .frames
+                .iter()
+                .find(|&&frame| frame as usize >= self.frames.len())
+            {
+                return Err(error(&format!("refers to nonexistent frame {}", frame)));
+            }
+            if animation.loops && (animation.loopback_index >= animation.frames.len()) {
+                return Err(error("has an invalid loopback index"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates an animator builder holding all animations of the atlas.
+    pub fn animator_builder(&self) -> GameResult<AnimatorBuilder> {
+        let mut builder = AnimatorBuilder::new();
+        for animation in self.animations.iter() {
+            builder.add_animation(
+                &animation.name,
+                &animation.frames,
+                animation.loops,
+                animation.loopback_index,
+                animation.ms_per_frame,
+            )?;
+        }
+        Ok(builder)
+    }
+}
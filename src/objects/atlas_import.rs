@@ -0,0 +1,339 @@
+use super::atlas_format::{atlas_error, AtlasAnimationData, AtlasData, AtlasFrameData};
+use crate::paths::resolve;
+use ggez::GameResult;
+use serde::de::{Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use std::fmt;
+
+/// Frame duration used when an export does not have any, in
+/// milliseconds.
+const DEFAULT_DURATION: u64 = 100;
+
+#[derive(Deserialize)]
+struct JsonRect {
+    x: f32,
+    y: f32,
+    w: f32,
+    h: f32,
+}
+
+#[derive(Deserialize)]
+struct JsonSize {
+    w: f32,
+    h: f32,
+}
+
+#[derive(Deserialize)]
+struct JsonPoint {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonFrame {
+    #[serde(default)]
+    filename: String,
+    frame: JsonRect,
+    #[serde(default)]
+    rotated: bool,
+    sprite_source_size: Option<JsonRect>,
+    source_size: Option<JsonSize>,
+    pivot: Option<JsonPoint>,
+    duration: Option<u64>,
+}
+
+/// Frames stored as an object, keyed by their file names. Keeps the
+/// order they appear on the file, which animation tags rely on.
+struct JsonFrameHash(Vec<JsonFrame>);
+
+impl<'de> Deserialize<'de> for JsonFrameHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct HashVisitor;
+
+        impl<'de> Visitor<'de> for HashVisitor {
+            type Value = JsonFrameHash;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of frames")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::new();
+                while let Some((filename, mut frame)) = map.next_entry::<String, JsonFrame>()? {
+                    frame.filename = filename;
+                    frames.push(frame);
+                }
+                Ok(JsonFrameHash(frames))
+            }
+        }
+
+        deserializer.deserialize_map(HashVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonFrames {
+    Array(Vec<JsonFrame>),
+    Hash(JsonFrameHash),
+}
+
+#[derive(Deserialize)]
+struct JsonTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+    repeat: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonMeta {
+    image: String,
+    #[serde(default)]
+    frame_tags: Vec<JsonTag>,
+}
+
+#[derive(Deserialize)]
+struct JsonSheet {
+    frames: JsonFrames,
+    meta: JsonMeta,
+}
+
+/// Imports a sprite sheet exported by Aseprite or TexturePacker in
+/// JSON format, either with frames as an array or as a hash.
+///
+/// The conversion follows these rules:
+/// - Frames keep the order they have on the file. Rotated frames are
+///   not supported.
+/// - Pivots are given relative to the untrimmed frame, and default to
+///   its center, so that trimming does not move sprites around.
+/// - Aseprite tags become animations, playing forward, in reverse or
+///   back and forth. Tags which repeat a limited number of times do
+///   not loop. The animation speed is taken from the duration of the
+///   first frame of the tag. TexturePacker exports have no tags.
+/// - The image path is relative to the exported file.
+///
+/// The name is used to resolve the image path and to describe errors.
+pub fn import(name: &str, contents: &str) -> GameResult<AtlasData> {
+    let sheet: JsonSheet = serde_json::from_str(contents)
+        .map_err(|e| atlas_error(name, format!("invalid sprite sheet: {}", e)))?;
+    let frames = match sheet.frames {
+        JsonFrames::Array(frames) => frames,
+        JsonFrames::Hash(JsonFrameHash(frames)) => frames,
+    };
+
+    let mut data = AtlasData {
+        image: resolve(name, &sheet.meta.image),
+        frames: Vec::with_capacity(frames.len()),
+        animations: Vec::new(),
+    };
+    for frame in frames.iter() {
+        if frame.rotated {
+            return Err(atlas_error(
+                name,
+                format!(
+                    "frame {} is rotated, which is not supported",
+                    frame.filename
+                ),
+            ));
+        }
+        let rect = &frame.frame;
+        let (offset, size) = match (&frame.sprite_source_size, &frame.source_size) {
+            (Some(trimmed), Some(source)) => ([trimmed.x, trimmed.y], [source.w, source.h]),
+            _ => ([0.0, 0.0], [rect.w, rect.h]),
+        };
+        let (pivot_x, pivot_y) = frame
+            .pivot
+            .as_ref()
+            .map(|pivot| (pivot.x, pivot.y))
+            .unwrap_or((0.5, 0.5));
+        data.frames.push(AtlasFrameData {
+            name: frame.filename.clone(),
+            rect: [rect.x, rect.y, rect.w, rect.h],
+            pivot: Some([size[0] * pivot_x - offset[0], size[1] * pivot_y - offset[1]]),
+        });
+    }
+
+    for tag in sheet.meta.frame_tags.iter() {
+        if (tag.from > tag.to) || (tag.to >= frames.len()) {
+            return Err(atlas_error(
+                name,
+                format!("tag {} refers to nonexistent frames", tag.name),
+            ));
+        }
+        // Back and forth sequences do not repeat their first and last
+        // frames when turning around
+        let forward: Vec<u32> = ((tag.from as u32)..=(tag.to as u32)).collect();
+        let backward: Vec<u32> = forward.iter().rev().copied().collect();
+        let inner = forward.len().saturating_sub(2);
+        let sequence: Vec<u32> = match tag.direction.as_str() {
+            "reverse" => backward,
+            "pingpong" => [&forward[..], &backward[1..][..inner]].concat(),
+            "pingpong_reverse" => [&backward[..], &forward[1..][..inner]].concat(),
+            _ => forward,
+        };
+        data.animations.push(AtlasAnimationData {
+            name: tag.name.clone(),
+            frames: sequence,
+            loops: tag.repeat.is_none(),
+            loopback_index: 0,
+            ms_per_frame: frames[tag.from].duration.unwrap_or(DEFAULT_DURATION),
+        });
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASEPRITE_ARRAY: &str = r#"{
+        "frames": [
+            {
+                "filename": "walk 0.aseprite",
+                "frame": {"x": 0, "y": 0, "w": 20, "h": 30},
+                "rotated": false,
+                "trimmed": true,
+                "spriteSourceSize": {"x": 6, "y": 2, "w": 20, "h": 30},
+                "sourceSize": {"w": 32, "h": 32},
+                "duration": 80
+            },
+            {
+                "filename": "walk 1.aseprite",
+                "frame": {"x": 20, "y": 0, "w": 32, "h": 32},
+                "rotated": false,
+                "trimmed": false,
+                "spriteSourceSize": {"x": 0, "y": 0, "w": 32, "h": 32},
+                "sourceSize": {"w": 32, "h": 32},
+                "duration": 100
+            },
+            {
+                "filename": "walk 2.aseprite",
+                "frame": {"x": 52, "y": 0, "w": 32, "h": 32},
+                "duration": 100
+            },
+            {
+                "filename": "walk 3.aseprite",
+                "frame": {"x": 84, "y": 0, "w": 32, "h": 32},
+                "duration": 100
+            }
+        ],
+        "meta": {
+            "image": "player.png",
+            "frameTags": [
+                {"name": "walk", "from": 0, "to": 3, "direction": "pingpong"},
+                {"name": "back", "from": 1, "to": 3, "direction": "pingpong_reverse"},
+                {"name": "once", "from": 1, "to": 2, "direction": "reverse", "repeat": "1"}
+            ]
+        }
+    }"#;
+
+    const ASEPRITE_HASH: &str = r#"{
+        "frames": {
+            "walk 1.aseprite": {
+                "frame": {"x": 20, "y": 0, "w": 32, "h": 32},
+                "duration": 100
+            },
+            "walk 0.aseprite": {
+                "frame": {"x": 0, "y": 0, "w": 20, "h": 30},
+                "spriteSourceSize": {"x": 6, "y": 2, "w": 20, "h": 30},
+                "sourceSize": {"w": 32, "h": 32},
+                "duration": 80
+            }
+        },
+        "meta": {
+            "image": "player.png",
+            "frameTags": [{"name": "walk", "from": 0, "to": 1, "direction": "forward"}]
+        }
+    }"#;
+
+    const TEXTURE_PACKER: &str = r#"{
+        "frames": {
+            "spring.png": {
+                "frame": {"x": 0, "y": 16, "w": 16, "h": 8},
+                "rotated": false,
+                "trimmed": true,
+                "spriteSourceSize": {"x": 0, "y": 8, "w": 16, "h": 8},
+                "sourceSize": {"w": 16, "h": 16},
+                "pivot": {"x": 0.5, "y": 1.0}
+            },
+            "arrow.png": {
+                "frame": {"x": 0, "y": 0, "w": 16, "h": 16},
+                "rotated": false,
+                "trimmed": false,
+                "spriteSourceSize": {"x": 0, "y": 0, "w": 16, "h": 16},
+                "sourceSize": {"w": 16, "h": 16}
+            }
+        },
+        "meta": {"app": "https://www.codeandweb.com/texturepacker", "image": "../objects.png"}
+    }"#;
+
+    fn frame_names(data: &AtlasData) -> Vec<&str> {
+        data.frames
+            .iter()
+            .map(|frame| frame.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn recomputes_pivots_of_trimmed_frames() {
+        let data = import("/sprites/player.json", ASEPRITE_ARRAY).unwrap();
+        assert_eq!(data.image, "/sprites/player.png");
+        assert_eq!(data.frames[0].rect, [0.0, 0.0, 20.0, 30.0]);
+        // The center of the untrimmed frame, relative to the trimmed one
+        assert_eq!(data.frames[0].pivot, Some([10.0, 14.0]));
+        assert_eq!(data.frames[1].pivot, Some([16.0, 16.0]));
+        assert_eq!(data.frames[2].pivot, Some([16.0, 16.0]));
+    }
+
+    #[test]
+    fn converts_tags_to_animations() {
+        let data = import("/sprites/player.json", ASEPRITE_ARRAY).unwrap();
+        let walk = &data.animations[0];
+        assert_eq!(walk.name, "walk");
+        assert_eq!(walk.frames, vec![0, 1, 2, 3, 2, 1]);
+        assert!(walk.loops);
+        assert_eq!(walk.ms_per_frame, 80);
+
+        let back = &data.animations[1];
+        assert_eq!(back.frames, vec![3, 2, 1, 2]);
+        assert_eq!(back.ms_per_frame, 100);
+
+        let once = &data.animations[2];
+        assert_eq!(once.frames, vec![2, 1]);
+        assert!(!once.loops);
+    }
+
+    #[test]
+    fn keeps_order_of_frame_hashes() {
+        let data = import("/sprites/player.json", ASEPRITE_HASH).unwrap();
+        assert_eq!(
+            frame_names(&data),
+            vec!["walk 1.aseprite", "walk 0.aseprite"]
+        );
+        assert_eq!(data.frames[1].pivot, Some([10.0, 14.0]));
+        assert_eq!(data.animations[0].frames, vec![0, 1]);
+        assert_eq!(data.animations[0].ms_per_frame, 100);
+    }
+
+    #[test]
+    fn imports_texture_packer_sheets() {
+        let data = import("/sprites/objects/objects.json", TEXTURE_PACKER).unwrap();
+        assert_eq!(data.image, "/sprites/objects.png");
+        assert_eq!(frame_names(&data), vec!["spring.png", "arrow.png"]);
+        // Pivot on the bottom of the untrimmed frame
+        assert_eq!(data.frames[0].pivot, Some([8.0, 8.0]));
+        assert_eq!(data.frames[1].pivot, Some([8.0, 8.0]));
+        assert!(data.animations.is_empty());
+    }
+
+    #[test]
+    fn rejects_tags_outside_of_frames() {
+        let contents = ASEPRITE_HASH.replace(r#""to": 1"#, r#""to": 2"#);
+        assert!(import("/sprites/player.json", &contents).is_err());
+    }
+}
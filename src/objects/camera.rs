@@ -4,6 +4,14 @@ use ggez::Context;
 use ggez::GameResult;
 use glam::*;
 
+/// Fraction of the distance to their target that camera bounds
+/// move on each frame.
+const BOUNDS_EASE_FACTOR: f32 = 0.125;
+
+/// Minimum and maximum distance camera bounds move on each frame,
+/// in pixels.
+const BOUNDS_EASE_SPEED: (f32, f32) = (2.0, 16.0);
+
 /// Represents data related to camera.
 ///
 /// Remember that this is not a component. Each screen should have
@@ -21,12 +29,82 @@ pub struct Camera {
     pub vertical_behaviour: CameraVerticalBehaviour,
     /// Displacement behaviour of the camera.
     pub displacement_behaviour: CameraDisplacementBehaviour,
+    /// Limits of the level area which can be seen by the camera. These
+    /// ease towards the target bounds, or the arena lock if any.
+    pub bounds: CameraBounds,
+    target: CameraBounds,
+    lock: Option<CameraBounds>,
     raw_position: Vec2,
     border: Rect,
     center: Vec2,
     displacement: Vec2,
 }
 
+/// Describes the limits of the area the camera can show, in
+/// level coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraBounds {
+    /// Top left corner of the visible area.
+    pub min: Vec2,
+    /// Bottom right corner of the visible area.
+    pub max: Vec2,
+}
+
+impl Default for CameraBounds {
+    fn default() -> Self {
+        Self {
+            min: Vec2::ZERO,
+            max: Vec2::splat(f32::MAX),
+        }
+    }
+}
+
+/// Describes a region of a level with its own camera bounds, which
+/// apply while the followed object is inside of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraRegion {
+    /// Area of the level covered by the region.
+    pub area: Rect,
+    /// Camera bounds used inside the region.
+    pub bounds: CameraBounds,
+}
+
+impl CameraRegion {
+    /// Checks whether a point is inside the region.
+    pub fn contains(&self, point: Vec2) -> bool {
+        (point.x >= self.area.left())
+            && (point.x < self.area.right())
+            && (point.y >= self.area.top())
+            && (point.y < self.area.bottom())
+    }
+}
+
+/// Moves a camera bound towards its target, given the edge of the
+/// area currently visible.
+///
+/// Bounds which are beyond the visible area are first brought to
+/// its edge, so that shrinking from far away does not take long.
+fn ease_bound(current: f32, target: f32, visible: f32, is_min: bool) -> f32 {
+    let shrinking = if is_min {
+        target > current
+    } else {
+        target < current
+    };
+    let current = match (shrinking, is_min) {
+        (true, true) => current.max(visible).min(target),
+        (true, false) => current.min(visible).max(target),
+        _ => current,
+    };
+    let distance = target - current;
+    let step =
+        (distance.abs() * BOUNDS_EASE_FACTOR).clamp(BOUNDS_EASE_SPEED.0, BOUNDS_EASE_SPEED.1);
+    if distance.abs() <= step {
+        target
+    } else {
+        current + step * distance.signum()
+    }
+}
+
 /// Describes the vertical behaviour of the camera.
 ///
 /// This behaviour mostly relates to how the Player is behaving
@@ -90,18 +168,96 @@ impl Camera {
             center: get_screen_center(context),
             vertical_behaviour: CameraVerticalBehaviour::RespectBounds,
             displacement_behaviour: CameraDisplacementBehaviour::None,
+            bounds: CameraBounds::default(),
+            target: CameraBounds::default(),
+            lock: None,
             displacement: Vec2::ZERO,
         }
     }
 
+    /// Immediately moves the camera to a certain position, discarding
+    /// any displacement.
+    pub fn reset(&mut self, position: &Position) {
+        self.raw_position = position.0;
+        self.displacement = Vec2::ZERO;
+        self.position.0 = self.clamp(self.raw_position);
+    }
+
+    /// Sets the bounds the camera eases towards, such as the bounds of
+    /// the level region the followed object is in.
+    pub fn set_bounds(&mut self, bounds: CameraBounds) {
+        self.target = bounds;
+    }
+
+    /// Immediately changes the camera bounds, releasing any arena lock.
+    pub fn snap_bounds(&mut self, bounds: CameraBounds) {
+        self.target = bounds;
+        self.bounds = bounds;
+        self.lock = None;
+    }
+
+    /// Gets the bounds the camera eases towards, ignoring arena locks.
+    pub fn target_bounds(&self) -> CameraBounds {
+        self.target
+    }
+
+    /// Locks the camera into an arena, so that it eases towards the
+    /// given bounds regardless of the target bounds.
+    pub fn lock(&mut self, bounds: CameraBounds) {
+        self.lock = Some(bounds);
+    }
+
+    /// Releases the arena lock, so that the camera eases back to the
+    /// target bounds.
+    pub fn release(&mut self) {
+        self.lock = None;
+    }
+
+    /// Moves the camera bounds towards the arena lock, or towards the
+    /// target bounds if not locked.
+    fn ease_bounds(&mut self) {
+        let goal = self.lock.unwrap_or(self.target);
+        let view = self.view_rect();
+        self.bounds = CameraBounds {
+            min: glam::vec2(
+                ease_bound(self.bounds.min.x, goal.min.x, view.left(), true),
+                ease_bound(self.bounds.min.y, goal.min.y, view.top(), true),
+            ),
+            max: glam::vec2(
+                ease_bound(self.bounds.max.x, goal.max.x, view.right(), false),
+                ease_bound(self.bounds.max.y, goal.max.y, view.bottom(), false),
+            ),
+        };
+    }
+
+    /// Clamps a camera position so that it respects the camera bounds.
+    fn clamp(&self, position: Vec2) -> Vec2 {
+        let min = self.bounds.min + self.center;
+        let max = (self.bounds.max - self.center).max(min);
+        position.max(min).min(max)
+    }
+
     /// Transform a vertex so that it is contained on screen.GameResult
     ///
     /// This function should be used to recalculate the position of
-    /// any elements being drawn on screen.
+    /// any elements being drawn on screen. Drawing functions taking a
+    /// `transform` argument expect this function, so that whatever
+    /// they draw follows the camera.
     pub fn transform(&self, vertex: Vec2) -> Vec2 {
         (vertex - self.position.0) + self.center
     }
 
+    /// Calculates the area of the level which is currently visible.
+    pub fn view_rect(&self) -> Rect {
+        let top_left = self.position.0 - self.center;
+        Rect::new(
+            top_left.x,
+            top_left.y,
+            self.center.x * 2.0,
+            self.center.y * 2.0,
+        )
+    }
+
     fn boundaries(&self) -> (f32, f32, f32, f32) {
         let pos = &self.raw_position;
         (
@@ -192,8 +348,9 @@ impl Camera {
         };
 
         // Define position considering displacement. Also prevent
-        // going beyond minimum position
-        self.position.0 = (self.raw_position + self.displacement).max(self.center);
+        // going beyond camera bounds, which ease towards their goal
+        self.ease_bounds();
+        self.position.0 = self.clamp(self.raw_position + self.displacement);
 
         Ok(())
     }
@@ -0,0 +1,106 @@
+use super::camera::CameraBounds;
+use super::general::{centered_rect, overlaps, Position};
+use super::player::{PlayerSensors, PlayerState};
+use ggez::graphics::{self, Color, DrawMode, MeshBuilder};
+use ggez::{Context, GameResult};
+use glam::*;
+use legion::*;
+
+/// Amount of frames a checkpoint spins for after activation.
+const SPIN_FRAMES: u32 = 32;
+
+/// Height of a checkpoint pole, in pixels.
+const POLE_HEIGHT: f32 = 48.0;
+
+/// Refers to a checkpoint (star post) component.
+///
+/// Checkpoints are numbered on the order they appear on the level,
+/// so that activating a checkpoint also spends all checkpoints
+/// before it.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Checkpoint {
+    /// Order of the checkpoint on the level.
+    pub index: u32,
+    /// Whether this checkpoint cannot be activated anymore.
+    pub spent: bool,
+    /// Remaining frames for the spin animation.
+    pub spin: u32,
+}
+
+/// Represents the information saved when a checkpoint is activated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CheckpointRecord {
+    /// Order of the activated checkpoint on the level.
+    pub index: u32,
+    /// Position where players should respawn.
+    pub position: Position,
+    /// Elapsed level time on activation, in frames.
+    pub time: u64,
+    /// Camera bounds on activation.
+    pub camera_bounds: CameraBounds,
+}
+
+impl Checkpoint {
+    /// Updates all checkpoints in the world, given the index of the
+    /// last activated checkpoint, if any.
+    ///
+    /// Checkpoints that come before the last activated one are spent
+    /// automatically. Returns the index and position of a checkpoint,
+    /// if one was activated by a player on this frame.
+    pub fn update(world: &mut World, last: Option<u32>) -> GameResult<Option<(u32, Position)>> {
+        let hitboxes: Vec<_> = <(&PlayerState, &Position)>::query()
+            .iter(world)
+            .filter(|(state, _)| !state.is_dead())
+            .map(|(state, position)| PlayerSensors::world_hitbox(state, position))
+            .collect();
+
+        let mut activated = None;
+        let mut query = <(&mut Checkpoint, &Position)>::query();
+        for (checkpoint, position) in query.iter_mut(world) {
+            checkpoint.spin = checkpoint.spin.saturating_sub(1);
+            if last.map(|last| checkpoint.index <= last).unwrap_or(false) {
+                checkpoint.spent = true;
+            }
+            if checkpoint.spent {
+                continue;
+            }
+
+            let area = centered_rect(position, glam::vec2(16.0, POLE_HEIGHT));
+            if hitboxes.iter().any(|hitbox| overlaps(hitbox, &area)) {
+                checkpoint.spent = true;
+                checkpoint.spin = SPIN_FRAMES;
+                activated = Some((checkpoint.index, *position));
+            }
+        }
+
+        Ok(activated)
+    }
+
+    /// Draws all checkpoints in the world.
+    pub fn draw<F>(world: &World, context: &mut Context, transform: F) -> GameResult
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        let mut query = <(&Checkpoint, &Position)>::query();
+        for (checkpoint, position) in query.iter(world) {
+            let base = transform(position.0) + glam::vec2(0.0, POLE_HEIGHT / 2.0);
+            let top = base - glam::vec2(0.0, POLE_HEIGHT);
+
+            // The head spins around the top of the pole
+            let angle = (checkpoint.spin as f32 / SPIN_FRAMES as f32) * std::f32::consts::PI * 4.0;
+            let head = top + glam::vec2(angle.sin() * 8.0, -angle.cos() * 8.0);
+            let head_color = if checkpoint.spent {
+                Color::new(1.0, 0.2, 0.2, 1.0)
+            } else {
+                Color::new(0.2, 0.4, 1.0, 1.0)
+            };
+
+            let mesh = MeshBuilder::new()
+                .line(&[base, top], 2.0, Color::new(0.4, 0.4, 0.8, 1.0))?
+                .circle(DrawMode::fill(), head, 4.0, 0.1, head_color)?
+                .build(context)?;
+            graphics::draw(context, &mesh, (Vec2::ZERO, 0.0, Color::WHITE))?;
+        }
+        Ok(())
+    }
+}
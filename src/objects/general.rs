@@ -1,6 +1,9 @@
 #![allow(clippy::from_over_into)]
 
+use ggez::graphics::Rect;
 use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Refers to a tag component which can be attached to any entity.
 ///
@@ -33,6 +36,24 @@ impl Position {
     }
 }
 
+/// Creates a rectangle of a certain size, centered on a position.
+pub fn centered_rect(position: &Position, size: Vec2) -> Rect {
+    Rect::new(
+        position.0.x - size.x / 2.0,
+        position.0.y - size.y / 2.0,
+        size.x,
+        size.y,
+    )
+}
+
+/// Checks whether two rectangles overlap, including their borders.
+pub fn overlaps(a: &Rect, b: &Rect) -> bool {
+    (a.left() <= b.right())
+        && (a.right() >= b.left())
+        && (a.top() <= b.bottom())
+        && (a.bottom() >= b.top())
+}
+
 /// Represents a direction for anything. May be converted to
 /// a unitary float value for rendering purposes.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -56,3 +77,39 @@ impl Default for Direction {
         Direction::Right
     }
 }
+
+/// Refers to the drawing priority component.
+///
+/// Entities with low priority are drawn behind high priority
+/// foreground tiles, such as the front half of a loop.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    #[default]
+    Low,
+    High,
+}
+
+/// Refers to the draw layer component.
+///
+/// Sprites of the same priority are drawn in the order of their
+/// layers, so that sprites on higher layers are in front. Sprites on
+/// the same layer are drawn in the order their draw layers were
+/// created, which keeps the order stable between frames. Sprites
+/// without a draw layer are not drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DrawLayer {
+    pub layer: i32,
+    order: u64,
+}
+
+impl DrawLayer {
+    /// Creates a draw layer, ordered after any other on the same layer.
+    pub fn new(layer: i32) -> Self {
+        static NEXT_ORDER: AtomicU64 = AtomicU64::new(1);
+        Self {
+            layer,
+            order: NEXT_ORDER.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use super::general::{centered_rect, overlaps, Position};
+use super::player::{
+    PlayerAction, PlayerConstants, PlayerSensors, PlayerShield, PlayerSpeed, PlayerState,
+};
+use ggez::graphics::{self, Color, DrawMode, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use glam::*;
+use legion::*;
+use serde::{Deserialize, Serialize};
+
+/// Describes the direction a spike object is pointing to.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpikeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Refers to a spike object component.
+///
+/// Spikes are solid objects centered on their position, which only
+/// hurt the player when touched from their pointy side.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spikes {
+    /// Direction the spikes are pointing to.
+    pub direction: SpikeDirection,
+    /// Size of the spikes, in pixels.
+    pub size: Vec2,
+}
+
+/// Describes the kind of damage a hazard deals.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HazardKind {
+    /// Lava and fire. Fire shields protect against it.
+    Lava,
+    /// Electricity. Lightning shields protect against it.
+    Electric,
+}
+
+/// Refers to a hazard area component.
+///
+/// Hazards are non-solid areas centered on their position which hurt
+/// the player on touch, unless the player has the proper shield.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hazard {
+    /// Kind of damage dealt by the hazard.
+    pub kind: HazardKind,
+    /// Size of the hazard area, in pixels.
+    pub size: Vec2,
+}
+
+impl Hazard {
+    /// Whether a certain shield protects against this hazard.
+    pub fn immune(&self, shield: PlayerShield) -> bool {
+        matches!(
+            (self.kind, shield),
+            (HazardKind::Lava, PlayerShield::Fire)
+                | (HazardKind::Electric, PlayerShield::Lightning)
+        )
+    }
+}
+
+/// Describes the side of a solid object which was touched.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Pushes the player out of a solid rectangle, returning the side of
+/// the rectangle which was touched, if any.
+fn solid_collision(
+    state: &mut PlayerState,
+    position: &mut Position,
+    speed: &mut PlayerSpeed,
+    solid: &Rect,
+) -> Option<Side> {
+    let hitbox = PlayerSensors::world_hitbox(state, position);
+
+    // Grounded players probe one extra pixel below, so they are able
+    // to keep standing on top of objects
+    let mut probe = hitbox;
+    if state.ground {
+        probe.h += 1.0;
+    }
+
+    if !overlaps(&probe, solid) {
+        return None;
+    }
+
+    let pushes = [
+        (Side::Top, hitbox.bottom() - solid.top()),
+        (Side::Bottom, solid.bottom() - hitbox.top()),
+        (Side::Left, hitbox.right() - solid.left()),
+        (Side::Right, solid.right() - hitbox.left()),
+    ];
+    let (side, push) = pushes
+        .iter()
+        .copied()
+        .fold((Side::Top, f32::MAX), |min, current| {
+            if current.1 < min.1 {
+                current
+            } else {
+                min
+            }
+        });
+
+    match side {
+        Side::Top => {
+            position.0.y -= push;
+            if !state.ground && (speed.ysp >= 0.0) {
+                speed.angle = 0.0;
+                state.set_ground(true, speed, true);
+                speed.ysp = 0.0;
+            }
+            state.on_object = state.ground;
+        }
+        Side::Bottom => {
+            position.0.y += push;
+            speed.ysp = speed.ysp.max(0.0);
+        }
+        Side::Left => {
+            position.0.x -= push;
+            if speed.xsp > 0.0 {
+                speed.xsp = 0.0;
+                speed.gsp = 0.0;
+            }
+        }
+        Side::Right => {
+            position.0.x += push;
+            if speed.xsp < 0.0 {
+                speed.xsp = 0.0;
+                speed.gsp = 0.0;
+            }
+        }
+    }
+
+    Some(side)
+}
+
+/// Performs collision between players, spikes and hazards.
+pub fn update(world: &mut World) -> GameResult {
+    let spikes: Vec<(Spikes, Position)> = <(&Spikes, &Position)>::query()
+        .iter(world)
+        .map(|(spikes, position)| (*spikes, *position))
+        .collect();
+    let hazards: Vec<(Hazard, Position)> = <(&Hazard, &Position)>::query()
+        .iter(world)
+        .map(|(hazard, position)| (*hazard, *position))
+        .collect();
+
+    let mut query = <(
+        &mut PlayerState,
+        &PlayerConstants,
+        &mut Position,
+        &mut PlayerSpeed,
+    )>::query();
+    for (state, constants, position, speed) in query.iter_mut(world) {
+        if state.is_dead() {
+            continue;
+        }
+
+        let was_on_object = state.on_object;
+        state.on_object = false;
+
+        for (spike, spike_position) in spikes.iter() {
+            let solid = centered_rect(spike_position, spike.size);
+            let knock_left = position.0.x < spike_position.0.x;
+
+            let pointy = solid_collision(state, position, speed, &solid)
+                == Some(match spike.direction {
+                    SpikeDirection::Up => Side::Top,
+                    SpikeDirection::Down => Side::Bottom,
+                    SpikeDirection::Left => Side::Left,
+                    SpikeDirection::Right => Side::Right,
+                });
+
+            // The Sonic 1 spike bug ignores invulnerability frames,
+            // except while the player is still being knocked back
+            let vulnerable = if constants.spike_bug {
+                state.action != PlayerAction::Hurt
+            } else {
+                !state.is_invulnerable()
+            };
+
+            if pointy && vulnerable {
+                state.hurt(speed, knock_left);
+            }
+        }
+
+        // Fall if the object the player was standing on is gone
+        if was_on_object && !state.on_object && state.ground {
+            state.set_ground(false, speed, false);
+        }
+
+        let hitbox = PlayerSensors::world_hitbox(state, position);
+        for (hazard, hazard_position) in hazards.iter() {
+            let area = centered_rect(hazard_position, hazard.size);
+            if overlaps(&hitbox, &area) && !hazard.immune(state.shield) && !state.is_invulnerable()
+            {
+                state.hurt(speed, position.0.x < hazard_position.0.x);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Draws all spikes and hazards in the world.
+pub fn draw<F>(world: &World, context: &mut Context, transform: F) -> GameResult
+where
+    F: Fn(Vec2) -> Vec2,
+{
+    let mut builder = MeshBuilder::new();
+    let mut empty = true;
+
+    let mut query = <(&Spikes, &Position)>::query();
+    for (spikes, position) in query.iter(world) {
+        let rect = centered_rect(&Position::wrap(transform(position.0)), spikes.size);
+        let (base, length, count) = match spikes.direction {
+            SpikeDirection::Up | SpikeDirection::Down => (rect.w, rect.h, (rect.w / 8.0) as usize),
+            SpikeDirection::Left | SpikeDirection::Right => {
+                (rect.h, rect.w, (rect.h / 8.0) as usize)
+            }
+        };
+        let step = base / count.max(1) as f32;
+        for i in 0..count.max(1) {
+            let start = i as f32 * step;
+            let (a, b, tip) = match spikes.direction {
+                SpikeDirection::Up => (
+                    glam::vec2(rect.x + start, rect.bottom()),
+                    glam::vec2(rect.x + start + step, rect.bottom()),
+                    glam::vec2(rect.x + start + step / 2.0, rect.bottom() - length),
+                ),
+                SpikeDirection::Down => (
+                    glam::vec2(rect.x + start, rect.top()),
+                    glam::vec2(rect.x + start + step, rect.top()),
+                    glam::vec2(rect.x + start + step / 2.0, rect.top() + length),
+                ),
+                SpikeDirection::Left => (
+                    glam::vec2(rect.right(), rect.y + start),
+                    glam::vec2(rect.right(), rect.y + start + step),
+                    glam::vec2(rect.right() - length, rect.y + start + step / 2.0),
+                ),
+                SpikeDirection::Right => (
+                    glam::vec2(rect.left(), rect.y + start),
+                    glam::vec2(rect.left(), rect.y + start + step),
+                    glam::vec2(rect.left() + length, rect.y + start + step / 2.0),
+                ),
+            };
+            builder.triangles(&[a, b, tip], Color::new(0.75, 0.75, 0.8, 1.0))?;
+            empty = false;
+        }
+    }
+
+    let mut query = <(&Hazard, &Position)>::query();
+    for (hazard, position) in query.iter(world) {
+        let rect = centered_rect(&Position::wrap(transform(position.0)), hazard.size);
+        let color = match hazard.kind {
+            HazardKind::Lava => Color::new(1.0, 0.3, 0.0, 0.7),
+            HazardKind::Electric => Color::new(1.0, 1.0, 0.3, 0.7),
+        };
+        builder.rectangle(DrawMode::fill(), rect, color)?;
+        empty = false;
+    }
+
+    if !empty {
+        let mesh = builder.build(context)?;
+        graphics::draw(context, &mesh, (Vec2::ZERO, 0.0, Color::WHITE))?;
+    }
+    Ok(())
+}
@@ -1,5 +1,17 @@
+pub mod afterimage;
 pub mod animation;
+pub mod arena;
+pub mod atlas_format;
+pub mod atlas_import;
 pub mod camera;
+pub mod checkpoint;
 pub mod general;
+pub mod hazards;
+pub mod object_manager;
+pub mod palette;
+pub mod particles;
+pub mod path_swapper;
 pub mod player;
+pub mod signpost;
 pub mod sprite_atlas;
+pub mod water;
@@ -0,0 +1,252 @@
+use super::arena::ArenaLock;
+use super::checkpoint::Checkpoint;
+use super::general::Position;
+use super::hazards::{Hazard, Spikes};
+use super::path_swapper::PathSwapper;
+use super::signpost::Signpost;
+use ggez::graphics::Rect;
+use legion::*;
+
+/// Distance around the visible area where objects are still kept
+/// alive, in pixels.
+const SPAWN_MARGIN: f32 = 128.0;
+
+/// Describes a kind of object which can be placed on a level,
+/// holding the component that is spawned for it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ObjectKind {
+    Spikes(Spikes),
+    Hazard(Hazard),
+    Checkpoint(Checkpoint),
+    Signpost(Signpost),
+    PathSwapper(PathSwapper),
+    ArenaLock(ArenaLock),
+}
+
+/// Describes the placement of an object on a level layout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ObjectPlacement {
+    /// Kind of the placed object.
+    pub kind: ObjectKind,
+    /// Position of the placed object.
+    pub position: Position,
+}
+
+/// Refers to a tag component for entities spawned by an object
+/// manager, holding the index of their placement on the layout.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ManagedObject(pub usize);
+
+/// Remembered state of a single object placement.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PlacementState {
+    /// Entity currently spawned for this placement, if any.
+    entity: Option<Entity>,
+    /// Whether the object was destroyed or collected, and should
+    /// never be spawned again.
+    destroyed: bool,
+    /// Object state to be used on the next spawn.
+    kind: ObjectKind,
+}
+
+/// Manages spawning and despawning of level objects.
+///
+/// Objects are only spawned as entities when they get close to the
+/// area visible by the camera, and are despawned when they get far
+/// from it. The state of despawned objects is remembered, so that
+/// spent or destroyed objects stay that way.
+pub struct ObjectManager {
+    layout: Vec<ObjectPlacement>,
+    states: Vec<PlacementState>,
+    order: Vec<usize>,
+}
+
+impl ObjectManager {
+    /// Creates a new object manager from an object layout.
+    pub fn new(layout: Vec<ObjectPlacement>) -> Self {
+        let mut manager = Self {
+            layout: Vec::new(),
+            states: Vec::new(),
+            order: Vec::new(),
+        };
+        for placement in layout {
+            manager.add(placement);
+        }
+        manager
+    }
+
+    /// Adds an object placement to the layout, returning its index.
+    pub fn add(&mut self, placement: ObjectPlacement) -> usize {
+        let index = self.layout.len();
+        self.layout.push(placement);
+        self.states.push(PlacementState {
+            entity: None,
+            destroyed: false,
+            kind: placement.kind,
+        });
+
+        // Keep placements ordered by their horizontal position
+        let layout = &self.layout;
+        let at = self
+            .order
+            .partition_point(|&other| layout[other].position.0.x <= placement.position.0.x);
+        self.order.insert(at, index);
+        index
+    }
+
+    /// Numbers checkpoints from 1 on the order they appear on the
+    /// layout, from left to right, including spawned checkpoints.
+    pub fn renumber_checkpoints(&mut self, world: &mut World) {
+        let mut index = 0;
+        for &placement in &self.order {
+            if let ObjectKind::Checkpoint(checkpoint) = &mut self.layout[placement].kind {
+                index += 1;
+                checkpoint.index = index;
+            } else {
+                continue;
+            }
+            let state = &mut self.states[placement];
+            if let ObjectKind::Checkpoint(checkpoint) = &mut state.kind {
+                checkpoint.index = index;
+            }
+            let mut entry = match state.entity.and_then(|entity| world.entry(entity)) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            if let Ok(checkpoint) = entry.get_component_mut::<Checkpoint>() {
+                checkpoint.index = index;
+            }
+        }
+    }
+
+    /// Gets the index of the checkpoint placed at a certain position,
+    /// if there is any.
+    pub fn checkpoint_index(&self, position: Position) -> Option<u32> {
+        self.layout
+            .iter()
+            .find_map(|placement| match placement.kind {
+                ObjectKind::Checkpoint(checkpoint) if placement.position == position => {
+                    Some(checkpoint.index)
+                }
+                _ => None,
+            })
+    }
+
+    /// Despawns all managed objects and forgets their state, so
+    /// that they can be spawned again as they were on the layout.
+    /// Meant for when an act is started.
+    pub fn reset(&mut self, world: &mut World) {
+        self.respawn(world);
+        for state in self.states.iter_mut() {
+            state.destroyed = false;
+        }
+    }
+
+    /// Despawns all managed objects and forgets their state, except
+    /// for objects which were destroyed or collected, which are never
+    /// spawned again. Meant for when players respawn.
+    pub fn respawn(&mut self, world: &mut World) {
+        for (state, placement) in self.states.iter_mut().zip(self.layout.iter()) {
+            if let Some(entity) = state.entity.take() {
+                world.remove(entity);
+            }
+            state.kind = placement.kind;
+        }
+    }
+
+    /// Spawns objects which entered the area around the camera view,
+    /// and despawns objects which left it.
+    pub fn update(&mut self, world: &mut World, view: Rect) {
+        let window = Rect::new(
+            view.x - SPAWN_MARGIN,
+            view.y - SPAWN_MARGIN,
+            view.w + SPAWN_MARGIN * 2.0,
+            view.h + SPAWN_MARGIN * 2.0,
+        );
+        let inside = |position: &Position| {
+            (position.0.x >= window.left())
+                && (position.0.x <= window.right())
+                && (position.0.y >= window.top())
+                && (position.0.y <= window.bottom())
+        };
+
+        // Despawn objects which left the window, and remember objects
+        // which were removed from the world by someone else
+        for (index, state) in self.states.iter_mut().enumerate() {
+            if let Some(entity) = state.entity {
+                if !world.contains(entity) {
+                    state.entity = None;
+                    state.destroyed = true;
+                } else if !inside(&self.layout[index].position) {
+                    state.kind = Self::remember(world, entity, state.kind);
+                    world.remove(entity);
+                    state.entity = None;
+                }
+            }
+        }
+
+        // Spawn objects within the window, looking only at the range
+        // of placements that horizontally fit in it
+        let layout = &self.layout;
+        let start = self
+            .order
+            .partition_point(|&index| layout[index].position.0.x < window.left());
+        let end = self
+            .order
+            .partition_point(|&index| layout[index].position.0.x <= window.right());
+        for &index in &self.order[start..end] {
+            let state = &mut self.states[index];
+            let position = self.layout[index].position;
+            if state.entity.is_none() && !state.destroyed && inside(&position) {
+                state.entity = Some(Self::spawn(world, state.kind, position, index));
+            }
+        }
+    }
+
+    fn spawn(world: &mut World, kind: ObjectKind, position: Position, index: usize) -> Entity {
+        let tag = ManagedObject(index);
+        match kind {
+            ObjectKind::Spikes(spikes) => world.push((spikes, position, tag)),
+            ObjectKind::Hazard(hazard) => world.push((hazard, position, tag)),
+            ObjectKind::Checkpoint(checkpoint) => world.push((checkpoint, position, tag)),
+            ObjectKind::Signpost(signpost) => world.push((signpost, position, tag)),
+            ObjectKind::PathSwapper(swapper) => world.push((swapper, position, tag)),
+            ObjectKind::ArenaLock(arena) => world.push((arena, position, tag)),
+        }
+    }
+
+    /// Reads back the state of an object from its entity.
+    fn remember(world: &World, entity: Entity, kind: ObjectKind) -> ObjectKind {
+        let entry = match world.entry_ref(entity) {
+            Ok(entry) => entry,
+            Err(_) => return kind,
+        };
+        match kind {
+            ObjectKind::Spikes(_) => entry
+                .get_component::<Spikes>()
+                .ok()
+                .map(|c| ObjectKind::Spikes(*c)),
+            ObjectKind::Hazard(_) => entry
+                .get_component::<Hazard>()
+                .ok()
+                .map(|c| ObjectKind::Hazard(*c)),
+            ObjectKind::Checkpoint(_) => entry
+                .get_component::<Checkpoint>()
+                .ok()
+                .map(|c| ObjectKind::Checkpoint(*c)),
+            ObjectKind::Signpost(_) => entry
+                .get_component::<Signpost>()
+                .ok()
+                .map(|c| ObjectKind::Signpost(*c)),
+            ObjectKind::PathSwapper(_) => entry
+                .get_component::<PathSwapper>()
+                .ok()
+                .map(|c| ObjectKind::PathSwapper(*c)),
+            ObjectKind::ArenaLock(_) => entry
+                .get_component::<ArenaLock>()
+                .ok()
+                .map(|c| ObjectKind::ArenaLock(*c)),
+        }
+        .unwrap_or(kind)
+    }
+}
@@ -0,0 +1,234 @@
+use super::sprite_atlas::SpriteAtlas;
+use ggez::graphics::Image;
+use ggez::{filesystem, Context, GameError, GameResult};
+use legion::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Describes an alternative palette, as replacements for the base
+/// colors of an image, in the same order.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaletteSwapData {
+    pub name: String,
+    pub colors: Vec<[u8; 3]>,
+}
+
+/// Describes a palette cycle, which shows a sequence of palettes
+/// over and over.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaletteCycleData {
+    pub name: String,
+    /// Names of the palettes shown by the cycle, in order.
+    pub palettes: Vec<String>,
+    /// Time each palette is shown for, in game frames.
+    pub duration: u32,
+}
+
+/// Describes the palettes of an image, as a sidecar file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaletteData {
+    /// Colors of the image which are replaced by palettes. Any other
+    /// color is kept as it is.
+    pub colors: Vec<[u8; 3]>,
+    #[serde(default)]
+    pub palettes: Vec<PaletteSwapData>,
+    #[serde(default)]
+    pub cycles: Vec<PaletteCycleData>,
+    /// Name of the palette or cycle selected at first, if any.
+    #[serde(default)]
+    pub initial: Option<String>,
+}
+
+fn palette_error(name: &str, message: String) -> GameError {
+    GameError::ResourceLoadError(format!("Palette {}: {}", name, message))
+}
+
+impl PaletteData {
+    /// Loads palette data from a JSON file on the resources directory.
+    pub fn load(context: &mut Context, path: &str) -> GameResult<Self> {
+        let file = filesystem::open(context, path)?;
+        let data: Self = serde_json::from_reader(file)
+            .map_err(|e| palette_error(path, format!("invalid palette file: {}", e)))?;
+        data.validate(path)?;
+        Ok(data)
+    }
+
+    /// Checks palette data for inconsistencies, such as palettes of
+    /// the wrong size and cycles referring to nonexistent palettes.
+    pub fn validate(&self, name: &str) -> GameResult {
+        let exists = |palette: &str| self.palettes.iter().any(|other| other.name == palette);
+        for palette in self.palettes.iter() {
+            if palette.colors.len() != self.colors.len() {
+                return Err(palette_error(
+                    name,
+                    format!(
+                        "palette {} has {} colors (expected {})",
+                        palette.name,
+                        palette.colors.len(),
+                        self.colors.len()
+                    ),
+                ));
+            }
+        }
+        for cycle in self.cycles.iter() {
+            let error =
+                |message: String| palette_error(name, format!("cycle {} {}", cycle.name, message));
+            if exists(&cycle.name) {
+                return Err(error("has the same name as a palette".to_string()));
+            }
+            if cycle.palettes.is_empty() || (cycle.duration == 0) {
+                return Err(error("is empty".to_string()));
+            }
+            if let Some(palette) = cycle.palettes.iter().find(|palette| !exists(palette)) {
+                return Err(error(format!("refers to nonexistent palette {}", palette)));
+            }
+        }
+        if let Some(initial) = &self.initial {
+            if !exists(initial) && !self.cycles.iter().any(|cycle| cycle.name == *initial) {
+                return Err(palette_error(
+                    name,
+                    format!("initial palette {} does not exist", initial),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Refers to a palette component.
+///
+/// Holds a copy of an image recolored with each palette, baked when
+/// the component is created, so that switching palettes only swaps
+/// textures. Palettes and cycles are selected by name, and cycles
+/// count game frames rather than elapsed time, so that they play the
+/// same way every time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    /// Baked images, where the first one has the base colors.
+    images: Vec<Image>,
+    /// Names of the palettes, in the order of the baked images.
+    names: Vec<String>,
+    cycles: Vec<PaletteCycleData>,
+    selected: Option<String>,
+    /// Selection before the current one, which can be restored.
+    previous: Option<String>,
+    /// Images shown by the selected palette or cycle, in order.
+    steps: Vec<usize>,
+    duration: u32,
+    timer: u32,
+    current: usize,
+}
+
+impl Palette {
+    /// Creates a palette component for an image, baking all palettes.
+    pub fn new(context: &mut Context, image: &Image, data: &PaletteData) -> GameResult<Self> {
+        let pixels = image.to_rgba8(context)?;
+        let indices: HashMap<[u8; 3], usize> = data
+            .colors
+            .iter()
+            .enumerate()
+            .map(|(index, color)| (*color, index))
+            .collect();
+
+        let mut images = vec![image.clone()];
+        for palette in data.palettes.iter() {
+            let mut baked = pixels.clone();
+            for pixel in baked.chunks_exact_mut(4) {
+                if let Some(&index) = indices.get(&[pixel[0], pixel[1], pixel[2]]) {
+                    pixel[..3].copy_from_slice(&palette.colors[index]);
+                }
+            }
+            let mut baked = Image::from_rgba8(context, image.width(), image.height(), &baked)?;
+            baked.set_filter(image.filter());
+            images.push(baked);
+        }
+
+        let mut palette = Self {
+            images,
+            names: data
+                .palettes
+                .iter()
+                .map(|palette| palette.name.clone())
+                .collect(),
+            cycles: data.cycles.clone(),
+            selected: None,
+            previous: None,
+            steps: vec![0],
+            duration: 1,
+            timer: 0,
+            current: 0,
+        };
+        palette.select(data.initial.as_deref());
+        Ok(palette)
+    }
+
+    /// Gets the index of the baked image for a palette.
+    fn find(&self, name: &str) -> Option<usize> {
+        self.names
+            .iter()
+            .position(|other| other == name)
+            .map(|index| index + 1)
+    }
+
+    /// Selects a palette or cycle by its name, or the base colors if
+    /// there is no name. Selecting what is already selected does not
+    /// restart cycles, and unknown names select the base colors.
+    pub fn select(&mut self, name: Option<&str>) {
+        if self.selected.as_deref() == name {
+            return;
+        }
+        let cycle = name.and_then(|name| self.cycles.iter().find(|cycle| cycle.name == name));
+        let (steps, duration) = match cycle {
+            Some(cycle) => (
+                cycle
+                    .palettes
+                    .iter()
+                    .map(|palette| self.find(palette).unwrap_or(0))
+                    .collect(),
+                cycle.duration,
+            ),
+            None => (vec![name.and_then(|name| self.find(name)).unwrap_or(0)], 1),
+        };
+        self.previous = std::mem::replace(&mut self.selected, name.map(str::to_string));
+        self.steps = steps;
+        self.duration = duration;
+        self.timer = 0;
+    }
+
+    /// Gets the name of the selected palette or cycle, if any.
+    pub fn selected(&self) -> Option<&str> {
+        self.selected.as_deref()
+    }
+
+    /// Selects again whatever was selected before the current palette
+    /// or cycle, such as the initial one.
+    pub fn restore(&mut self) {
+        let previous = self.previous.clone();
+        self.select(previous.as_deref());
+    }
+
+    /// Advances the selected cycle by a single frame.
+    ///
+    /// Returns the image to be shown, if it changed.
+    pub fn update(&mut self) -> Option<&Image> {
+        let step = (self.timer / self.duration) as usize % self.steps.len();
+        self.timer = self.timer.wrapping_add(1);
+        let image = self.steps[step];
+        if image == self.current {
+            return None;
+        }
+        self.current = image;
+        self.images.get(image)
+    }
+
+    /// Updates the palettes of all sprites in the world, swapping the
+    /// textures of their sprite atlases when needed.
+    pub fn update_sprites(world: &mut World) {
+        let mut query = <(&mut Palette, &mut SpriteAtlas)>::query();
+        for (palette, atlas) in query.iter_mut(world) {
+            if let Some(image) = palette.update() {
+                atlas.set_texture(image.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use super::atlas_format::{atlas_error, AtlasData};
+use super::general::Position;
+use super::sprite_atlas::SpriteAtlas;
+use ggez::{Context, GameResult};
+use glam::*;
+use legion::*;
+use std::collections::HashMap;
+
+/// Path to the atlas holding the graphics of all particles.
+const PARTICLES_PATH: &str = "/sprites/particles.json";
+
+/// Seed for the random deviations of particles.
+const PARTICLES_SEED: u32 = 0x2545_f491;
+
+/// Enumeration for describing the kinds of particles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleKind {
+    /// Dust left behind while skidding.
+    Dust,
+    /// Dust thrown back while charging a spin dash.
+    SpinDashDust,
+    /// Droplets thrown when entering or leaving the water.
+    Splash,
+    /// Sparkle left by a collected ring.
+    Sparkle,
+    /// Star trailing behind an invincible player.
+    Star,
+}
+
+impl ParticleKind {
+    /// All kinds of particles, each of which needs an animation on
+    /// the particle atlas.
+    const ALL: [ParticleKind; 5] = [
+        ParticleKind::Dust,
+        ParticleKind::SpinDashDust,
+        ParticleKind::Splash,
+        ParticleKind::Sparkle,
+        ParticleKind::Star,
+    ];
+
+    /// Gets the name of the animation of the particle on the
+    /// particle atlas.
+    fn animation(&self) -> &'static str {
+        match self {
+            ParticleKind::Dust => "dust",
+            ParticleKind::SpinDashDust => "spindash_dust",
+            ParticleKind::Splash => "splash",
+            ParticleKind::Sparkle => "sparkle",
+            ParticleKind::Star => "star",
+        }
+    }
+
+    /// Gets the amount of frames a particle lives for.
+    fn lifetime(&self) -> u32 {
+        match self {
+            ParticleKind::Dust => 16,
+            ParticleKind::SpinDashDust => 12,
+            ParticleKind::Splash => 24,
+            ParticleKind::Sparkle => 16,
+            ParticleKind::Star => 32,
+        }
+    }
+
+    /// Gets the downwards acceleration of a particle, in pixels per
+    /// frame squared.
+    fn gravity(&self) -> f32 {
+        match self {
+            ParticleKind::Splash => 0.21875,
+            _ => 0.0,
+        }
+    }
+}
+
+/// A single particle, which moves on its own until its lifetime ends.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Particle {
+    kind: ParticleKind,
+    position: Vec2,
+    velocity: Vec2,
+    age: u32,
+}
+
+/// Animation of a particle, with its frame duration in game frames.
+#[derive(Clone, Debug, PartialEq)]
+struct ParticleAnimation {
+    frames: Vec<u32>,
+    duration: u32,
+    loops: bool,
+}
+
+/// Refers to a particle emitter component.
+///
+/// Emitters attached to entities follow their position. Emitters may
+/// also be placed on fixed positions of the level through a particle
+/// system, which removes them once they run out of emissions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticleEmitter {
+    pub kind: ParticleKind,
+    /// Whether the emitter is creating particles.
+    pub active: bool,
+    /// Offset from the emitter position where particles appear.
+    pub offset: Vec2,
+    /// Initial velocity of particles, in pixels per frame.
+    pub velocity: Vec2,
+    /// Largest random deviation of the initial velocity of particles,
+    /// on each axis.
+    pub spread: Vec2,
+    /// Frames between emissions.
+    pub interval: u32,
+    /// Amount of particles created on each emission.
+    pub count: u32,
+    /// Emissions left, if limited.
+    pub remaining: Option<u32>,
+    timer: u32,
+}
+
+impl ParticleEmitter {
+    /// Creates an inactive emitter which creates a single particle
+    /// every few frames while active.
+    pub fn new(kind: ParticleKind, interval: u32) -> Self {
+        Self {
+            kind,
+            active: false,
+            offset: Vec2::ZERO,
+            velocity: Vec2::ZERO,
+            spread: Vec2::ZERO,
+            interval,
+            count: 1,
+            remaining: None,
+            timer: 0,
+        }
+    }
+
+    /// Creates an emitter which creates a few particles at once, and
+    /// then stops.
+    pub fn burst(kind: ParticleKind, count: u32, velocity: Vec2, spread: Vec2) -> Self {
+        Self {
+            active: true,
+            velocity,
+            spread,
+            count,
+            remaining: Some(1),
+            ..Self::new(kind, 1)
+        }
+    }
+
+    /// Whether the emitter will not create any more particles.
+    fn finished(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    /// Advances the emitter by a single frame, returning the amount
+    /// of particles to be created.
+    fn update(&mut self) -> u32 {
+        if !self.active || self.finished() {
+            self.timer = 0;
+            return 0;
+        }
+        let emit = self.timer == 0;
+        self.timer = (self.timer + 1) % self.interval.max(1);
+        if !emit {
+            return 0;
+        }
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= 1;
+        }
+        self.count
+    }
+}
+
+/// Simulates and draws particles, such as dust and splashes.
+///
+/// All particles share a single sprite atlas, so that they are drawn
+/// as a single batch. Particles are animated by counting game frames,
+/// and their random deviations come from a fixed seed, so that they
+/// behave the same way every time.
+pub struct ParticleSystem {
+    atlas: SpriteAtlas,
+    animations: HashMap<String, ParticleAnimation>,
+    particles: Vec<Particle>,
+    emitters: Vec<(ParticleEmitter, Vec2)>,
+    seed: u32,
+}
+
+impl ParticleSystem {
+    /// Creates a new particle system, loading the particle atlas.
+    pub fn new(context: &mut Context) -> GameResult<Self> {
+        let data = AtlasData::load(context, PARTICLES_PATH)?;
+        let atlas = SpriteAtlas::from_data(context, &data)?;
+        let animations = data
+            .animations
+            .iter()
+            .map(|animation| {
+                (
+                    animation.name.clone(),
+                    ParticleAnimation {
+                        frames: animation.frames.clone(),
+                        duration: ((animation.ms_per_frame * 60 + 500) / 1000).max(1) as u32,
+                        loops: animation.loops,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        if let Some(kind) = ParticleKind::ALL
+            .iter()
+            .find(|kind| !animations.contains_key(kind.animation()))
+        {
+            return Err(atlas_error(
+                PARTICLES_PATH,
+                format!(
+                    "missing animation {} for {:?} particles",
+                    kind.animation(),
+                    kind
+                ),
+            ));
+        }
+        Ok(Self {
+            atlas,
+            animations,
+            particles: Vec::new(),
+            emitters: Vec::new(),
+            seed: PARTICLES_SEED,
+        })
+    }
+
+    /// Removes all particles and emitters on fixed positions.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.emitters.clear();
+        self.seed = PARTICLES_SEED;
+    }
+
+    /// Places an emitter on a fixed position of the level.
+    pub fn add_emitter(&mut self, emitter: ParticleEmitter, position: Vec2) {
+        self.emitters.push((emitter, position));
+    }
+
+    /// Gets a pseudo-random number between -1 and 1.
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+
+    /// Creates particles for an emitter at a certain position.
+    fn spawn(&mut self, emitter: &ParticleEmitter, position: Vec2, count: u32) {
+        for _ in 0..count {
+            let deviation = glam::vec2(self.random(), self.random()) * emitter.spread;
+            self.particles.push(Particle {
+                kind: emitter.kind,
+                position: position + emitter.offset,
+                velocity: emitter.velocity + deviation,
+                age: 0,
+            });
+        }
+    }
+
+    /// Updates all emitters and particles by a single frame.
+    pub fn update(&mut self, world: &mut World) {
+        let mut query = <(&mut ParticleEmitter, &Position)>::query();
+        let mut spawned = Vec::new();
+        for (emitter, position) in query.iter_mut(world) {
+            let count = emitter.update();
+            if count > 0 {
+                spawned.push((*emitter, position.0, count));
+            }
+        }
+        for (emitter, position) in self.emitters.iter_mut() {
+            let count = emitter.update();
+            if count > 0 {
+                spawned.push((*emitter, *position, count));
+            }
+        }
+        self.emitters.retain(|(emitter, _)| !emitter.finished());
+        for (emitter, position, count) in spawned {
+            self.spawn(&emitter, position, count);
+        }
+
+        for particle in self.particles.iter_mut() {
+            particle.velocity.y += particle.kind.gravity();
+            particle.position += particle.velocity;
+            particle.age += 1;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.kind.lifetime());
+    }
+
+    /// Queues all particles for drawing, replacing anything queued
+    /// before.
+    pub fn queue<F>(&mut self, transform: F) -> GameResult
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        self.atlas.clear();
+        for particle in self.particles.iter() {
+            let animation = match self.animations.get(particle.kind.animation()) {
+                Some(animation) => animation,
+                None => continue,
+            };
+            let mut step = (particle.age / animation.duration) as usize;
+            if animation.loops {
+                step %= animation.frames.len();
+            } else {
+                step = step.min(animation.frames.len() - 1);
+            }
+            self.atlas.queue_draw(
+                animation.frames[step],
+                transform(particle.position).round(),
+                Vec2::ONE,
+                0.0,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Draws the queued particles.
+    pub fn draw(&self, context: &mut Context) -> GameResult {
+        self.atlas.draw(context)
+    }
+}
@@ -0,0 +1,124 @@
+use super::general::{Position, Priority};
+use super::player::{PlayerSpeed, PlayerState};
+use crate::level::collision::CollisionLayer;
+use ggez::graphics::{self, Color, MeshBuilder};
+use ggez::{Context, GameResult};
+use glam::*;
+use legion::*;
+use serde::{Deserialize, Serialize};
+
+/// Describes the orientation of the line a path swapper spans.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwapperOrientation {
+    /// Swapper is a vertical line, crossed from left to right or
+    /// from right to left.
+    Vertical,
+    /// Swapper is a horizontal line, crossed from top to bottom or
+    /// from bottom to top.
+    Horizontal,
+}
+
+/// Refers to an invisible path swapper component.
+///
+/// Path swappers are lines centered on their position. Whenever a
+/// player crosses them, its collision layer and draw priority are
+/// changed depending on the direction of the crossing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathSwapper {
+    /// Orientation of the swapper line.
+    pub orientation: SwapperOrientation,
+    /// Length of the swapper line, in pixels.
+    pub length: f32,
+    /// Layer set when crossing from left to right, or top to bottom.
+    pub layer_after: CollisionLayer,
+    /// Layer set when crossing from right to left, or bottom to top.
+    pub layer_before: CollisionLayer,
+    /// Priority set when crossing from left to right, or top to bottom.
+    pub priority_after: Priority,
+    /// Priority set when crossing from right to left, or bottom to top.
+    pub priority_before: Priority,
+    /// Whether the swapper only works for players on the ground.
+    pub ground_only: bool,
+}
+
+impl PathSwapper {
+    /// Updates the collision layer and priority of all players which
+    /// crossed a path swapper on this frame.
+    ///
+    /// Should be called after updating the player physics, so that
+    /// the previous position can be determined from the speed.
+    pub fn update(world: &mut World) -> GameResult {
+        let swappers: Vec<(PathSwapper, Position)> = <(&PathSwapper, &Position)>::query()
+            .iter(world)
+            .map(|(swapper, position)| (*swapper, *position))
+            .collect();
+
+        let mut query = <(&mut PlayerState, &PlayerSpeed, &Position, &mut Priority)>::query();
+        for (state, speed, position, priority) in query.iter_mut(world) {
+            let current = position.0;
+            let previous = current - glam::vec2(speed.xsp, speed.ysp);
+
+            for (swapper, swapper_position) in swappers.iter() {
+                if swapper.ground_only && !state.ground {
+                    continue;
+                }
+
+                // Project positions so that the swapper is always
+                // crossed along the first coordinate
+                let (line, span, before, after) = match swapper.orientation {
+                    SwapperOrientation::Vertical => (
+                        swapper_position.0.x,
+                        swapper_position.0.y,
+                        glam::vec2(previous.x, previous.y),
+                        glam::vec2(current.x, current.y),
+                    ),
+                    SwapperOrientation::Horizontal => (
+                        swapper_position.0.y,
+                        swapper_position.0.x,
+                        glam::vec2(previous.y, previous.x),
+                        glam::vec2(current.y, current.x),
+                    ),
+                };
+
+                if (after.y - span).abs() > swapper.length / 2.0 {
+                    continue;
+                }
+
+                if (before.x < line) && (after.x >= line) {
+                    state.layer = swapper.layer_after;
+                    *priority = swapper.priority_after;
+                } else if (before.x >= line) && (after.x < line) {
+                    state.layer = swapper.layer_before;
+                    *priority = swapper.priority_before;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draws all path swappers in the world, for debugging purposes.
+    pub fn debug_draw<F>(world: &World, context: &mut Context, transform: F) -> GameResult
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        let mut query = <(&PathSwapper, &Position)>::query();
+        for (swapper, position) in query.iter(world) {
+            let center = transform(position.0);
+            let half = match swapper.orientation {
+                SwapperOrientation::Vertical => glam::vec2(0.0, swapper.length / 2.0),
+                SwapperOrientation::Horizontal => glam::vec2(swapper.length / 2.0, 0.0),
+            };
+            let color = if swapper.ground_only {
+                Color::new(1.0, 0.5, 0.0, 0.8)
+            } else {
+                Color::new(0.0, 1.0, 0.5, 0.8)
+            };
+            let mesh = MeshBuilder::new()
+                .line(&[center - half, center + half], 2.0, color)?
+                .build(context)?;
+            graphics::draw(context, &mesh, (Vec2::ZERO, 0.0, Color::WHITE))?;
+        }
+        Ok(())
+    }
+}
@@ -1,14 +1,24 @@
 use super::{PlayerAction, PlayerSpeed, PlayerState};
+use crate::objects::afterimage::Afterimage;
 use crate::objects::animation::Animator;
+use crate::objects::palette::Palette;
+use crate::objects::particles::ParticleEmitter;
 use ggez::GameResult;
 use legion::*;
 
+/// Speed above which players leave afterimages behind.
+const AFTERIMAGE_SPEED: f32 = 9.95;
+
 /// Performs updates on the animation component of the player.
 pub fn update(world: &mut World) -> GameResult {
     let mut query = <(&PlayerState, &PlayerSpeed, &mut Animator)>::query();
     for (state, speed, animator) in query.iter_mut(world) {
         let gsp = speed.gsp.abs();
-        if state.ground {
+        if state.action == PlayerAction::Dead {
+            animator.set("dead".to_string());
+        } else if state.action == PlayerAction::Hurt {
+            animator.set("hurt".to_string());
+        } else if state.ground {
             animator.set(String::from(match state.action {
                 PlayerAction::LookingUp => "lookup",
                 PlayerAction::Crouching => "crouch",
@@ -36,8 +46,39 @@ pub fn update(world: &mut World) -> GameResult {
             animator.set_duration_ms((16.0 * (4.0 - gsp).max(1.0).floor()) as u64);
         }
 
-        // Update direction
+        // Update direction, and follow the ground angle unless airborne
+        // or rolling. The ground angle is counterclockwise, while the
+        // sprite rotation is clockwise
         animator.direction = state.direction;
+        if state.ground && (state.action != PlayerAction::Rolling) {
+            animator.set_rotation(-speed.angle);
+        } else {
+            animator.set_rotation(0.0);
+        }
+    }
+
+    // Shift colors while underwater, and restore the previous colors
+    // after leaving the water
+    let mut query = <(&PlayerState, &mut Palette)>::query();
+    for (state, palette) in query.iter_mut(world) {
+        if state.underwater {
+            palette.select(Some("underwater"));
+        } else if palette.selected() == Some("underwater") {
+            palette.restore();
+        }
+    }
+
+    // Leave dust behind while skidding
+    let mut query = <(&PlayerState, &mut ParticleEmitter)>::query();
+    for (state, emitter) in query.iter_mut(world) {
+        emitter.active = state.ground && (state.action == PlayerAction::Skidding);
+    }
+
+    // Leave afterimages behind at top speed
+    let mut query = <(&PlayerState, &PlayerSpeed, &mut Afterimage)>::query();
+    for (state, speed, afterimage) in query.iter_mut(world) {
+        let moving = glam::vec2(speed.xsp, speed.ysp).length() >= AFTERIMAGE_SPEED;
+        afterimage.active = moving && !state.is_dead();
     }
     Ok(())
 }
@@ -0,0 +1,203 @@
+use super::{PlayerAction, PlayerConstants, PlayerSpeed, PlayerState};
+use crate::level::collision::{CollisionMap, SensorDirection, SensorHit};
+use crate::objects::general::Position;
+use glam::*;
+
+/// Distance from the center of the player to its wall sensors.
+const PUSH_RADIUS: f32 = 10.0;
+
+/// Describes the current ground mode of a player, which determines
+/// the direction its ground sensors point to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GroundMode {
+    Floor,
+    RightWall,
+    Ceiling,
+    LeftWall,
+}
+
+impl GroundMode {
+    /// Determines the ground mode from a ground angle, in radians.
+    pub fn from_angle(angle: f32) -> Self {
+        let degrees = angle.to_degrees().rem_euclid(360.0);
+        if (degrees <= 45.0) || (degrees >= 315.0) {
+            GroundMode::Floor
+        } else if degrees < 135.0 {
+            GroundMode::RightWall
+        } else if degrees <= 225.0 {
+            GroundMode::Ceiling
+        } else {
+            GroundMode::LeftWall
+        }
+    }
+
+    /// Direction pointing towards the ground on this mode.
+    fn down(&self) -> SensorDirection {
+        match self {
+            GroundMode::Floor => SensorDirection::Down,
+            GroundMode::RightWall => SensorDirection::Right,
+            GroundMode::Ceiling => SensorDirection::Up,
+            GroundMode::LeftWall => SensorDirection::Left,
+        }
+    }
+
+    /// Direction the player moves towards when its ground speed is
+    /// positive on this mode.
+    fn forward(&self) -> SensorDirection {
+        match self {
+            GroundMode::Floor => SensorDirection::Right,
+            GroundMode::RightWall => SensorDirection::Up,
+            GroundMode::Ceiling => SensorDirection::Left,
+            GroundMode::LeftWall => SensorDirection::Down,
+        }
+    }
+}
+
+/// Calculates the width and height radii of the player.
+pub fn radii(state: &PlayerState) -> (f32, f32) {
+    match state.action {
+        PlayerAction::Rolling | PlayerAction::Jumping => (7.0, 14.0),
+        _ => (9.0, 19.0),
+    }
+}
+
+/// Casts a pair of sensors, returning the closest hit.
+fn sense_pair(
+    terrain: &CollisionMap,
+    state: &PlayerState,
+    origins: [Vec2; 2],
+    direction: SensorDirection,
+    max: f32,
+) -> Option<SensorHit> {
+    origins
+        .iter()
+        .filter_map(|origin| terrain.sense(state.layer, *origin, direction, max))
+        .fold(None, |closest: Option<SensorHit>, hit| match closest {
+            Some(closest) if closest.distance <= hit.distance => Some(closest),
+            _ => Some(hit),
+        })
+}
+
+/// Pushes the player away from walls towards a direction.
+fn wall_collision(
+    terrain: &CollisionMap,
+    state: &PlayerState,
+    position: &mut Position,
+    direction: SensorDirection,
+) -> bool {
+    match terrain.sense(state.layer, position.0, direction, PUSH_RADIUS) {
+        Some(hit) if hit.distance < PUSH_RADIUS => {
+            position.0 -= direction.vector() * (PUSH_RADIUS - hit.distance);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Performs collision between a player and the level terrain.
+///
+/// Should be called after the player position was updated.
+pub fn update(
+    terrain: &CollisionMap,
+    state: &mut PlayerState,
+    constants: &PlayerConstants,
+    position: &mut Position,
+    speed: &mut PlayerSpeed,
+) {
+    let (width_radius, height_radius) = radii(state);
+
+    if state.ground {
+        let mode = GroundMode::from_angle(speed.angle);
+        let down = mode.down().vector();
+        let forward = mode.forward().vector();
+
+        // Wall sensors, pointing towards where the player is moving
+        if speed.gsp != 0.0 {
+            let direction = if speed.gsp > 0.0 {
+                mode.forward()
+            } else {
+                mode.forward().opposite()
+            };
+            if wall_collision(terrain, state, position, direction) {
+                speed.gsp = 0.0;
+            }
+        }
+
+        // Ground sensors A and B
+        let origins = [
+            position.0 - forward * width_radius,
+            position.0 + forward * width_radius,
+        ];
+        let hit = sense_pair(terrain, state, origins, mode.down(), height_radius + 16.0);
+        let tolerance = (speed.xsp.abs().max(speed.ysp.abs()) + 4.0).min(14.0);
+        match hit {
+            Some(hit) if (hit.distance - height_radius) <= tolerance => {
+                if (hit.distance - height_radius) >= -14.0 {
+                    position.0 += down * (hit.distance - height_radius);
+                    speed.angle = hit.angle;
+                }
+            }
+            _ => {
+                if !state.on_object {
+                    state.set_ground(false, speed, false);
+                }
+            }
+        }
+
+        // Fall from walls and ceilings when too slow
+        if state.ground
+            && (GroundMode::from_angle(speed.angle) != GroundMode::Floor)
+            && (speed.gsp.abs() < constants.fall)
+        {
+            state.set_ground(false, speed, false);
+        }
+
+        if !state.ground {
+            speed.angle = 0.0;
+        }
+        return;
+    }
+
+    // Air wall sensors
+    if speed.xsp <= 0.0 && wall_collision(terrain, state, position, SensorDirection::Left) {
+        speed.xsp = 0.0;
+    }
+    if speed.xsp >= 0.0 && wall_collision(terrain, state, position, SensorDirection::Right) {
+        speed.xsp = 0.0;
+    }
+
+    let horizontal = glam::vec2(width_radius, 0.0);
+
+    // Ceiling sensors C and D
+    if speed.ysp < 0.0 {
+        let origins = [position.0 - horizontal, position.0 + horizontal];
+        if let Some(hit) = sense_pair(terrain, state, origins, SensorDirection::Up, height_radius) {
+            if hit.distance < height_radius {
+                position.0.y += height_radius - hit.distance;
+
+                // Attach to steep ceilings, bump on flat ones
+                match GroundMode::from_angle(hit.angle) {
+                    GroundMode::RightWall | GroundMode::LeftWall => {
+                        speed.angle = hit.angle;
+                        state.set_ground(true, speed, false);
+                    }
+                    _ => speed.ysp = 0.0,
+                }
+            }
+        }
+    }
+
+    // Ground sensors A and B
+    if speed.ysp >= 0.0 {
+        let origins = [position.0 - horizontal, position.0 + horizontal];
+        let max = height_radius + speed.ysp.max(1.0);
+        if let Some(hit) = sense_pair(terrain, state, origins, SensorDirection::Down, max) {
+            let depth = hit.distance - height_radius;
+            if (depth < 0.0) && (depth >= -(speed.ysp + 8.0)) {
+                position.0.y += depth;
+                speed.angle = hit.angle;
+                state.set_ground(true, speed, true);
+            }
+        }
+    }
+}
@@ -27,6 +27,10 @@ pub struct PlayerConstants {
     pub grv: f32,
     /// Minimum jump speed for when the jump button is released
     pub minjmp: f32,
+    /// Gravity while being knocked back after getting hurt
+    pub hurtgrv: f32,
+    /// Whether spikes ignore invulnerability frames (Sonic 1 spike bug)
+    pub spike_bug: bool,
 }
 
 impl Default for PlayerConstants {
@@ -46,6 +50,8 @@ impl Default for PlayerConstants {
             jmp: 6.5,
             grv: 0.21875,
             minjmp: -4.0,
+            hurtgrv: 0.1875,
+            spike_bug: false,
         }
     }
 }
@@ -58,4 +64,30 @@ impl PlayerConstants {
             ..Self::default()
         }
     }
+
+    /// Default constants for player, reproducing Sonic 1 quirks such
+    /// as the spike bug.
+    pub fn default_sonic1() -> Self {
+        Self {
+            spike_bug: true,
+            ..Self::default()
+        }
+    }
+
+    /// Constants for player while underwater, derived from the
+    /// regular ones. Movement is halved, jumps are 3 units weaker
+    /// and gravity is much lower.
+    pub fn underwater(&self) -> Self {
+        Self {
+            acc: self.acc / 2.0,
+            dec: self.dec / 2.0,
+            frc: self.frc / 2.0,
+            top: self.top / 2.0,
+            air: self.air / 2.0,
+            jmp: self.jmp - 3.0,
+            grv: 0.0625,
+            minjmp: -2.0,
+            ..*self
+        }
+    }
 }
@@ -1,7 +1,9 @@
 use super::PlayerConstants;
 use super::PlayerSpeed;
 use super::PlayerState;
-use crate::objects::general::Position;
+use crate::objects::afterimage::Afterimage;
+use crate::objects::animation::RotationMode;
+use crate::objects::general::{Position, Priority};
 use ggez::Context;
 use ggez::GameResult;
 use glam::*;
@@ -19,10 +21,18 @@ impl Player {
     ///
     /// This will also load player assets such as animation data
     /// and sprites. You can also determine whether you want it
-    /// to use Knuckles-related constants or not.
-    pub fn create(context: &mut Context, world: &mut World, knuckles: bool) -> GameResult<Entity> {
-        use crate::objects::animation::*;
+    /// to use Knuckles-related constants or not, and how the sprite
+    /// rotates on slopes.
+    pub fn create(
+        context: &mut Context,
+        world: &mut World,
+        knuckles: bool,
+        rotation: RotationMode,
+    ) -> GameResult<Entity> {
+        use crate::objects::atlas_format::*;
         use crate::objects::general::*;
+        use crate::objects::palette::*;
+        use crate::objects::particles::*;
         use crate::objects::sprite_atlas::*;
 
         let constants = if knuckles {
@@ -34,42 +44,54 @@ impl Player {
         let state = PlayerState::default();
         let position = Position::new(30.0, 240.0);
         let speed = PlayerSpeed::default();
-        let atlas = SpriteAtlas::new(context, "/sprites/sonic.png", Vec2::new(60.0, 60.0))?;
-
-        let mut animator = AnimatorBuilder::new()
-            .add_animation(
-                "idle",
-                &[
-                    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2,
-                    3, 3, 4, 4,
-                ],
-                true,
-                26,
-                125,
-            )?
-            .add_animation("walk", &[5, 6, 7, 8, 9, 10], true, 0, 100)?
-            .add_animation("run", &[11, 12, 13, 14], true, 0, 63)?
-            .add_animation("roll", &[15, 16, 17, 16, 19, 16, 21, 16], true, 0, 125)?
-            .add_animation("skid", &[23], true, 0, 1000)?
-            .add_animation("peel", &[24, 25, 26, 27], true, 0, 60)?
-            .add_animation("push", &[28, 29, 30, 31], true, 0, 500)?
-            .add_animation("crouch", &[32], true, 0, 1000)?
-            .add_animation("lookup", &[33], true, 0, 1000)?
-            .add_animation("dead", &[34], true, 0, 1000)?
-            .build();
+        let data = AtlasData::load(context, "/sprites/sonic.json")?;
+        let atlas = SpriteAtlas::from_data(context, &data)?;
+        let palette = PaletteData::load(context, "/sprites/sonic_palette.json")?;
+        let palette = Palette::new(context, atlas.texture(), &palette)?;
+        let mut animator = data.animator_builder()?.rotation_mode(rotation).build();
 
         animator.set("idle".to_string());
 
-        Ok(world.push((state, constants, position, speed, atlas, animator)))
+        // Dust puffs rise from the feet of the player
+        let mut dust = ParticleEmitter::new(ParticleKind::Dust, 4);
+        dust.offset = glam::vec2(0.0, 16.0);
+        dust.velocity = glam::vec2(0.0, -0.25);
+        let afterimage = Afterimage::new(&atlas, 4, 3);
+
+        let entity = world.push((
+            state,
+            constants,
+            position,
+            speed,
+            atlas,
+            animator,
+            Priority::default(),
+            DrawLayer::new(0),
+        ));
+        if let Some(mut entry) = world.entry(entity) {
+            entry.add_component(palette);
+            entry.add_component(dust);
+            entry.add_component(afterimage);
+        }
+        Ok(entity)
     }
 
-    /// Respawns all players in the world.
-    pub fn respawn_all(world: &mut World) {
-        let mut query = <(&mut PlayerState, &mut Position, &mut PlayerSpeed)>::query();
-        for (state, position, speed) in query.iter_mut(world) {
-            *position = Position::new(30.0, 240.0);
+    /// Respawns all players in the world, at a certain position.
+    pub fn respawn_all(world: &mut World, spawn: &Position) {
+        let mut query = <(
+            &mut PlayerState,
+            &mut Position,
+            &mut PlayerSpeed,
+            &mut Priority,
+        )>::query();
+        for (state, position, speed, priority) in query.iter_mut(world) {
+            *position = *spawn;
             *speed = PlayerSpeed::default();
             *state = PlayerState::default();
+            *priority = Priority::default();
+        }
+        for afterimage in <&mut Afterimage>::query().iter_mut(world) {
+            afterimage.clear();
         }
     }
 }
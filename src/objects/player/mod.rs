@@ -1,3 +1,4 @@
+mod collision;
 mod constants;
 mod general;
 mod sensors;
@@ -42,6 +43,10 @@ pub enum PlayerAction {
     LookingUp,
     /// Player is skidding
     Skidding,
+    /// Player was hurt and is being knocked back.
+    Hurt,
+    /// Player is dead and falling off the screen.
+    Dead,
 }
 
 impl Default for PlayerAction {
@@ -49,3 +54,19 @@ impl Default for PlayerAction {
         PlayerAction::Default
     }
 }
+
+/// Enumeration for describing the shield a player is carrying.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum PlayerShield {
+    /// Player has no shield.
+    #[default]
+    None,
+    /// Regular blue shield. Protects from a single hit.
+    Regular,
+    /// Fire shield. Also grants immunity to fire and lava.
+    Fire,
+    /// Lightning shield. Also grants immunity to electricity.
+    Lightning,
+    /// Bubble shield. Also allows breathing underwater.
+    Bubble,
+}
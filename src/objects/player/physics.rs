@@ -1,17 +1,24 @@
-use super::{PlayerAction, PlayerConstants, PlayerSpeed, PlayerState};
+use super::{collision, PlayerAction, PlayerConstants, PlayerSensors, PlayerSpeed, PlayerState};
 use crate::input::Input;
+use crate::level::collision::CollisionMap;
+use crate::objects::camera::CameraBounds;
+use crate::objects::general::Position;
 use ggez::GameResult;
 use legion::*;
 
-/// Y coordinate of a fake ground for the player.
-/// Should be removed once collision is working.
-pub const FAKE_GROUND_Y: f32 = 800.0;
+/// Closest distance the player can get to the left edge of the
+/// camera bounds, in pixels.
+const LEFT_BOUNDARY_MARGIN: f32 = 16.0;
+
+/// Closest distance the player can get to the right edge of the
+/// camera bounds, in pixels.
+const RIGHT_BOUNDARY_MARGIN: f32 = 24.0;
 
 /// Updates the player's logic based on the input.
 ///
 /// This is the entry point for updating anything related
-/// to physics, including movement and collision.
-pub fn update(world: &mut World, input: &Input) -> GameResult {
+/// to physics, including movement and terrain collision.
+pub fn update(world: &mut World, input: &Input, terrain: Option<&CollisionMap>) -> GameResult {
     use crate::input::InputButton;
     use crate::objects::general::*;
     let mut query = <(
@@ -21,18 +28,31 @@ pub fn update(world: &mut World, input: &Input) -> GameResult {
         &mut PlayerSpeed,
     )>::query();
     for (state, constants, position, speed) in query.iter_mut(world) {
-        let (up, down, left, right) = (
-            input.pressing(InputButton::Up),
-            input.pressing(InputButton::Down),
-            input.pressing(InputButton::Left),
-            input.pressing(InputButton::Right),
-        );
-
-        // Fake ground. Remove later!
-        if !state.ground && (position.0.y >= FAKE_GROUND_Y) {
-            position.0.y = FAKE_GROUND_Y;
-            speed.angle = 0.0;
-            state.set_ground(true, speed, true);
+        // Locked controls make the player ignore input and run right
+        let (up, down, left, right, stick, jump, hold_jump) = if state.autorun {
+            (false, false, false, true, 1.0, false, false)
+        } else {
+            (
+                input.pressing(InputButton::Up),
+                input.pressing(InputButton::Down),
+                input.pressing(InputButton::Left),
+                input.pressing(InputButton::Right),
+                input.left_stick().0.abs(),
+                input.pressed(InputButton::A),
+                input.pressing(InputButton::A),
+            )
+        };
+
+        // Dead players ignore input and collision, and just fall
+        if state.is_dead() {
+            speed.ysp += constants.grv;
+            position.0.y += speed.ysp;
+            continue;
+        }
+
+        // Count down invulnerability, but only after recovering
+        if (state.action != PlayerAction::Hurt) && (state.invulnerable > 0) {
+            state.invulnerable -= 1;
         }
 
         // Horizontal movement
@@ -58,10 +78,10 @@ pub fn update(world: &mut World, input: &Input) -> GameResult {
                     speed.gsp += if speed.gsp < 0.0 {
                         // Decelerate if moving left
                         state.action = PlayerAction::Skidding;
-                        constants.dec * input.left_stick().0.abs()
+                        constants.dec * stick
                     } else {
                         // Accelerate otherwise
-                        constants.acc * input.left_stick().0.abs()
+                        constants.acc * stick
                     };
 
                     if (state.action == PlayerAction::Skidding) && (speed.gsp >= 0.0) {
@@ -72,10 +92,10 @@ pub fn update(world: &mut World, input: &Input) -> GameResult {
                     speed.gsp -= if speed.gsp > 0.0 {
                         // Decelerate if moving right
                         state.action = PlayerAction::Skidding;
-                        constants.dec * input.left_stick().0.abs()
+                        constants.dec * stick
                     } else {
                         // Accelerate otherwise
-                        constants.acc * input.left_stick().0.abs()
+                        constants.acc * stick
                     };
 
                     if (state.action == PlayerAction::Skidding) && (speed.gsp <= 0.0) {
@@ -119,7 +139,7 @@ pub fn update(world: &mut World, input: &Input) -> GameResult {
             // Transform x and Y speed accordingly
             speed.xsp = speed.gsp * angle_cos;
             speed.ysp = speed.gsp * -angle_sin;
-        } else {
+        } else if state.action != PlayerAction::Hurt {
             // Air movement
             let dir = if right && !left {
                 1.0
@@ -160,15 +180,19 @@ pub fn update(world: &mut World, input: &Input) -> GameResult {
             }
 
             // Apply gravity
-            speed.ysp += constants.grv;
+            speed.ysp += if state.action == PlayerAction::Hurt {
+                constants.hurtgrv
+            } else {
+                constants.grv
+            };
 
             // Apply jump cap
-            if !input.pressing(InputButton::A) && (speed.ysp < constants.minjmp) {
+            if !hold_jump && (speed.ysp < constants.minjmp) {
                 speed.ysp = constants.minjmp;
             }
         } else {
             // Perform jump.
-            if input.pressed(InputButton::A) {
+            if jump {
                 state.set_ground(false, speed, true);
                 state.action = PlayerAction::Jumping;
                 speed.xsp -= constants.jmp * speed.angle.sin();
@@ -179,6 +203,48 @@ pub fn update(world: &mut World, input: &Input) -> GameResult {
         // Transform position
         position.0.x += speed.xsp;
         position.0.y += speed.ysp;
+
+        // Collide with terrain
+        if let Some(terrain) = terrain {
+            collision::update(terrain, state, constants, position, speed);
+        }
+    }
+    Ok(())
+}
+
+/// Keeps players inside the level boundaries, given by the current
+/// camera bounds.
+///
+/// Players are stopped by the left and right limits, and die as soon
+/// as they fall completely below the bottom limit, which is what
+/// makes bottomless pits. Players running off screen with locked
+/// controls, as after clearing an act, ignore the right limit.
+pub fn update_bounds(world: &mut World, bounds: &CameraBounds) -> GameResult {
+    let mut query = <(&mut PlayerState, &mut Position, &mut PlayerSpeed)>::query();
+    for (state, position, speed) in query.iter_mut(world) {
+        if state.is_dead() {
+            continue;
+        }
+
+        let left = bounds.min.x + LEFT_BOUNDARY_MARGIN;
+        let right = bounds.max.x - RIGHT_BOUNDARY_MARGIN;
+        if position.0.x < left {
+            position.0.x = left;
+            if speed.xsp < 0.0 {
+                speed.xsp = 0.0;
+                speed.gsp = 0.0;
+            }
+        } else if !state.autorun && (position.0.x > right) {
+            position.0.x = right;
+            if speed.xsp > 0.0 {
+                speed.xsp = 0.0;
+                speed.gsp = 0.0;
+            }
+        }
+
+        if PlayerSensors::world_hitbox(state, position).top() > bounds.max.y {
+            state.kill(speed);
+        }
     }
     Ok(())
 }
@@ -2,6 +2,7 @@ use super::PlayerAction;
 use super::PlayerSpeed;
 use super::PlayerState;
 use crate::objects::general::Position;
+use ggez::graphics::Rect;
 use ggez::Context;
 use ggez::GameResult;
 
@@ -12,6 +13,31 @@ use ggez::GameResult;
 pub struct PlayerSensors;
 
 impl PlayerSensors {
+    /// Whether the player is using its smaller hitbox and sensors.
+    fn is_small(state: &PlayerState) -> bool {
+        (state.action == PlayerAction::Rolling)
+            || (state.action == PlayerAction::Jumping)
+            || (state.action == PlayerAction::Crouching)
+    }
+
+    /// Calculates the player hitbox, relative to its position.
+    pub fn hitbox(state: &PlayerState) -> Rect {
+        if !Self::is_small(state) {
+            Rect::new(-8.0, -16.0, 17.0, 33.0)
+        } else if state.action == PlayerAction::Crouching {
+            Rect::new(-8.0, 1.0, 17.0, 17.0)
+        } else {
+            Rect::new(-8.0, -5.0, 17.0, 21.0)
+        }
+    }
+
+    /// Calculates the player hitbox in world coordinates.
+    pub fn world_hitbox(state: &PlayerState, position: &Position) -> Rect {
+        let mut rect = Self::hitbox(state);
+        rect.translate(position.0);
+        rect
+    }
+
     /// Draws a representation for player sensors. Requires player data
     /// such as its state, position and rotation.
     pub fn debug_draw(
@@ -21,9 +47,7 @@ impl PlayerSensors {
         speed: &PlayerSpeed,
     ) -> GameResult {
         use ggez::graphics::{self, Color, DrawMode, MeshBuilder};
-        let smaller = (state.action == PlayerAction::Rolling)
-            || (state.action == PlayerAction::Jumping)
-            || (state.action == PlayerAction::Crouching);
+        let smaller = Self::is_small(state);
 
         let mut crouch_offset = 0.0;
         if smaller {
@@ -145,25 +169,16 @@ impl PlayerSensors {
             // Build mesh
             .build(context)?;
 
-        let hitbox_rect =
-            if (state.action == PlayerAction::Rolling) || (state.action == PlayerAction::Jumping) {
-                graphics::Rect::new(-8.0, -10.0, 17.0, 21.0)
-            } else if state.action == PlayerAction::Crouching {
-                graphics::Rect::new(-8.0, -4.0, 17.0, 17.0)
-            } else {
-                graphics::Rect::new(-8.0, -16.0, 17.0, 33.0)
-            };
-
         let hitbox = MeshBuilder::new()
             .rectangle(
                 DrawMode::fill(),
-                hitbox_rect,
+                Self::hitbox(state),
                 Color::new(1.0, 0.0, 1.0, 0.1),
             )?
             .build(context)?;
 
-        graphics::draw(context, &hitbox, (hotspot, 0.0, Color::WHITE))?;
-        graphics::draw(context, &sensors, (hotspot, speed.angle, Color::WHITE))?;
+        graphics::draw(context, &hitbox, (position.0, 0.0, Color::WHITE))?;
+        graphics::draw(context, &sensors, (hotspot, -speed.angle, Color::WHITE))?;
         Ok(())
     }
 }
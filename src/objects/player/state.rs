@@ -1,7 +1,12 @@
 use super::PlayerAction;
+use super::PlayerShield;
 use super::PlayerSpeed;
+use crate::level::collision::CollisionLayer;
 use crate::objects::general::Direction;
 
+/// Amount of frames the player stays invulnerable after getting hurt.
+pub const INVULNERABILITY_FRAMES: u32 = 120;
+
 /// Represents the state variables for a player.
 ///
 /// These variables refer mostly to state such as ground state and
@@ -14,6 +19,20 @@ pub struct PlayerState {
     pub action: PlayerAction,
     /// Direction for the player
     pub direction: Direction,
+    /// Remaining frames of invulnerability after getting hurt
+    pub invulnerable: u32,
+    /// Shield currently carried by the player
+    pub shield: PlayerShield,
+    /// Amount of rings carried by the player
+    pub rings: u32,
+    /// Whether the player is standing on top of a solid object
+    pub on_object: bool,
+    /// Whether player control is locked, making the player run right
+    pub autorun: bool,
+    /// Collision layer the player is currently on
+    pub layer: CollisionLayer,
+    /// Whether the player is below the water surface
+    pub underwater: bool,
 }
 
 impl PlayerState {
@@ -21,22 +40,76 @@ impl PlayerState {
         self.ground
     }
 
+    /// Whether the player cannot be hurt right now.
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0 || self.action == PlayerAction::Hurt || self.is_dead()
+    }
+
+    /// Whether the player is dead.
+    pub fn is_dead(&self) -> bool {
+        self.action == PlayerAction::Dead
+    }
+
+    /// Hurts the player, regardless of invulnerability. The player loses
+    /// its shield or rings and is knocked back away from the source of
+    /// damage. If there is nothing to lose, the player dies instead.
+    pub fn hurt(&mut self, speed: &mut PlayerSpeed, knock_left: bool) {
+        if self.is_dead() {
+            return;
+        }
+
+        self.ground = false;
+        self.on_object = false;
+        speed.gsp = 0.0;
+        speed.angle = 0.0;
+
+        if (self.shield == PlayerShield::None) && (self.rings == 0) {
+            self.kill(speed);
+            speed.ysp = -7.0;
+            return;
+        }
+
+        if self.shield != PlayerShield::None {
+            self.shield = PlayerShield::None;
+        } else {
+            self.rings = 0;
+        }
+
+        self.action = PlayerAction::Hurt;
+        self.invulnerable = INVULNERABILITY_FRAMES;
+        speed.xsp = if knock_left { -2.0 } else { 2.0 };
+        speed.ysp = -4.0;
+    }
+
+    /// Kills the player immediately, regardless of shields and rings.
+    /// The player simply falls off the screen.
+    pub fn kill(&mut self, speed: &mut PlayerSpeed) {
+        if self.is_dead() {
+            return;
+        }
+        self.ground = false;
+        self.on_object = false;
+        self.action = PlayerAction::Dead;
+        speed.gsp = 0.0;
+        speed.angle = 0.0;
+        speed.xsp = 0.0;
+        speed.ysp = 0.0;
+    }
+
     /// Define the ground state. This will also update the player speed.
     /// Remember to set the player speed's angle to the ground angle
     /// BEFORE calling this function.
     pub fn set_ground(&mut self, mut state: bool, speed: &mut PlayerSpeed, downward: bool) {
         if !self.ground && state {
+            // Ground angle is stored in radians, but compared in degrees
+            let angle = speed.angle.to_degrees().rem_euclid(360.0);
             if downward {
                 // Shallow angle
-                if ((speed.angle >= 0.0) && (speed.angle <= 23.0))
-                    || ((speed.angle >= 339.0) && (speed.angle <= 360.0))
-                {
+                if (0.0..=23.0).contains(&angle) || (339.0..=360.0).contains(&angle) {
                     speed.gsp = speed.xsp
                 }
                 // Half steep
-                else if ((speed.angle > 23.0) && (speed.angle <= 45.0))
-                    || ((speed.angle >= 315.0) && (speed.angle < 339.0))
-                {
+                else if ((angle > 23.0) && (angle <= 45.0)) || (315.0..339.0).contains(&angle) {
                     speed.gsp = if speed.xsp.abs() > speed.ysp.abs() {
                         speed.xsp
                     } else {
@@ -44,9 +117,7 @@ impl PlayerState {
                     };
                 }
                 // Full steep
-                else if ((speed.angle > 45.0) && (speed.angle <= 90.0))
-                    || ((speed.angle >= 270.0) && (speed.angle < 315.0))
-                {
+                else if ((angle > 45.0) && (angle <= 90.0)) || (270.0..315.0).contains(&angle) {
                     speed.gsp = if speed.xsp.abs() > speed.ysp.abs() {
                         speed.xsp
                     } else {
@@ -56,14 +127,12 @@ impl PlayerState {
             } else {
                 // Going upward
                 // Slope
-                if ((speed.angle > 90.0) && (speed.angle <= 135.0))
-                    || ((speed.angle > 225.0) && (speed.angle <= 270.0))
-                {
+                if ((angle > 90.0) && (angle <= 135.0)) || ((angle > 225.0) && (angle <= 270.0)) {
                     // TODO: Attach to ceiling.
                     speed.gsp = speed.ysp * -speed.angle.sin().signum();
                 }
                 // Ceiling
-                else if (speed.angle > 135.0) && (speed.angle <= 225.0) {
+                else if (angle > 135.0) && (angle <= 225.0) {
                     speed.ysp = 0.0;
                     state = false;
                 }
@@ -72,6 +141,13 @@ impl PlayerState {
             if state && self.action == PlayerAction::Jumping {
                 self.action = PlayerAction::Default;
             }
+
+            // Landing after getting hurt stops the player completely
+            if state && self.action == PlayerAction::Hurt {
+                self.action = PlayerAction::Default;
+                speed.xsp = 0.0;
+                speed.gsp = 0.0;
+            }
         }
         self.ground = state;
     }
@@ -0,0 +1,113 @@
+use super::general::Position;
+use super::player::PlayerState;
+use ggez::graphics::{self, Color, DrawMode, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use glam::*;
+use legion::*;
+
+/// Amount of frames the signpost spins for before landing.
+const SPIN_FRAMES: u32 = 120;
+
+/// Describes the current state of a signpost.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub enum SignpostState {
+    /// Waiting for a player to pass by.
+    #[default]
+    Idle,
+    /// Spinning, with the amount of elapsed frames.
+    Spinning(u32),
+    /// Landed on the character's face.
+    Landed,
+}
+
+/// Refers to an end-of-act signpost component.
+///
+/// The signpost starts spinning when a player passes by it, and
+/// lands on the character's face afterwards.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct Signpost {
+    pub state: SignpostState,
+}
+
+impl Signpost {
+    /// Updates all signposts in the world.
+    ///
+    /// Returns the position of a signpost, if a player passed by it
+    /// on this frame.
+    pub fn update(world: &mut World) -> GameResult<Option<Position>> {
+        let players: Vec<Position> = <(&PlayerState, &Position)>::query()
+            .iter(world)
+            .filter(|(state, _)| !state.is_dead())
+            .map(|(_, position)| *position)
+            .collect();
+
+        let mut passed = None;
+        let mut query = <(&mut Signpost, &Position)>::query();
+        for (signpost, position) in query.iter_mut(world) {
+            signpost.state = match signpost.state {
+                SignpostState::Idle => {
+                    if players.iter().any(|player| player.0.x >= position.0.x) {
+                        passed = Some(*position);
+                        SignpostState::Spinning(0)
+                    } else {
+                        SignpostState::Idle
+                    }
+                }
+                SignpostState::Spinning(frames) if frames >= SPIN_FRAMES => SignpostState::Landed,
+                SignpostState::Spinning(frames) => SignpostState::Spinning(frames + 1),
+                SignpostState::Landed => SignpostState::Landed,
+            };
+        }
+
+        Ok(passed)
+    }
+
+    /// Checks whether every signpost in the world has landed.
+    pub fn all_landed(world: &World) -> bool {
+        <&Signpost>::query()
+            .iter(world)
+            .all(|signpost| signpost.state == SignpostState::Landed)
+    }
+
+    /// Draws all signposts in the world.
+    pub fn draw<F>(world: &World, context: &mut Context, transform: F) -> GameResult
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        let mut query = <(&Signpost, &Position)>::query();
+        for (signpost, position) in query.iter(world) {
+            let base = transform(position.0) + glam::vec2(0.0, 24.0);
+            let center = base - glam::vec2(0.0, 32.0);
+
+            // The board gets narrower and wider as it spins, and
+            // shows its back face half of the time
+            let (width, front) = match signpost.state {
+                SignpostState::Spinning(frames) => {
+                    let angle = frames as f32 * 0.25;
+                    ((angle.cos().abs() * 24.0).max(1.0), angle.cos() >= 0.0)
+                }
+                _ => (24.0, true),
+            };
+            let face = match signpost.state {
+                SignpostState::Landed => Color::new(0.1, 0.3, 1.0, 1.0),
+                _ if front => Color::new(0.9, 0.2, 0.2, 1.0),
+                _ => Color::new(0.9, 0.9, 0.2, 1.0),
+            };
+
+            let mesh = MeshBuilder::new()
+                .line(
+                    &[base, center + glam::vec2(0.0, 12.0)],
+                    2.0,
+                    Color::new(0.6, 0.6, 0.6, 1.0),
+                )?
+                .rectangle(
+                    DrawMode::fill(),
+                    Rect::new(center.x - width / 2.0, center.y - 12.0, width, 24.0),
+                    face,
+                )?
+                .build(context)?;
+            graphics::draw(context, &mesh, (Vec2::ZERO, 0.0, Color::WHITE))?;
+        }
+        Ok(())
+    }
+}
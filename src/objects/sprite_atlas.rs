@@ -1,73 +1,103 @@
+use super::atlas_format::AtlasData;
 use ggez::graphics::spritebatch::SpriteBatch;
-use ggez::graphics::{self, DrawParam, Image, Rect};
+use ggez::graphics::{self, Color, DrawParam, Image, Rect};
 use ggez::{Context, GameResult};
 use glam::*;
 
+/// A single frame of a sprite atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AtlasFrame {
+    /// Area of the frame on the image, in texels.
+    src: Rect,
+    /// Point of the frame drawn at the hotspot, in pixels.
+    pivot: Vec2,
+}
+
 /// A sprite atlas is a collection of frames that can be used to draw
 /// a single sprite.
 ///
-/// Frames on the sprite atlas are assumed to be arranged in a grid,
-/// numerated from 0 in the left-right, top-bottom order, respectively,
-/// and always equally spaced.
+/// Frames are described by atlas data, with arbitrary areas and
+/// pivots, and numerated from 0 in the order they appear on it.
 #[derive(Debug, Clone, PartialEq)]
 pub struct SpriteAtlas {
     texture: Image,
     batch: SpriteBatch,
-    frame_size: Vec2,
-    half_frame: Vec2,
+    frames: Vec<AtlasFrame>,
 }
 
 impl SpriteAtlas {
-    /// Creates a new sprite atlas from an image.
-    ///
-    /// The path to the image is relative to the `resources` directory,
-    /// and the frame size is required in pixels.
-    pub fn new(context: &mut Context, path: &str, frame_size: Vec2) -> GameResult<Self> {
-        let texture = Image::new(context, path)?;
-        let batch = SpriteBatch::new(texture.clone());
+    /// Creates a new sprite atlas from atlas data.
+    pub fn from_data(context: &mut Context, data: &AtlasData) -> GameResult<Self> {
+        let texture = Image::new(context, &data.image)?;
+        let image_size = glam::vec2(texture.width() as f32, texture.height() as f32);
+        let frames = data
+            .frames
+            .iter()
+            .map(|frame| {
+                let [x, y, w, h] = frame.rect;
+                AtlasFrame {
+                    src: Rect::new(
+                        x / image_size.x,
+                        y / image_size.y,
+                        w / image_size.x,
+                        h / image_size.y,
+                    ),
+                    pivot: Vec2::from(frame.pivot()),
+                }
+            })
+            .collect();
         Ok(Self {
+            batch: SpriteBatch::new(texture.clone()),
             texture,
-            batch,
-            frame_size,
-            half_frame: frame_size / 2.0,
+            frames,
         })
     }
 
-    /// Gets the size of the entire sprite atlas.
-    pub fn get_image_size(&self) -> Vec2 {
-        glam::vec2(self.texture.width() as f32, self.texture.height() as f32)
-    }
-
-    fn calculate_frame(&self, frame: u32) -> Rect {
-        let image_size = self.get_image_size();
-        let frames_per_line = image_size.x / self.frame_size.x;
-        let frame_line = (frame as f32 / frames_per_line).trunc();
-        let frame_column = frame as f32 % frames_per_line;
-        let frame_size_texels = self.frame_size / image_size;
-
-        Rect::new(
-            frame_column * frame_size_texels.x,
-            frame_line * frame_size_texels.y,
-            frame_size_texels.x,
-            frame_size_texels.y,
-        )
+    /// Queues a frame of the sprite atlas for drawing. Frames which do
+    /// not exist are not drawn.
+    ///
+    /// Requires the number of the frame, the position of the sprite on
+    /// screen, a scale factor related to each axis and a clockwise
+    /// rotation around the frame pivot, in radians.
+    pub fn queue_draw(
+        &mut self,
+        frame: u32,
+        hotspot: Vec2,
+        scale: Vec2,
+        rotation: f32,
+    ) -> GameResult {
+        self.queue_draw_tinted(frame, hotspot, scale, rotation, Color::WHITE)
     }
 
-    pub fn queue_draw(&mut self, frame: u32, hotspot: Vec2, scale: Vec2) -> GameResult {
-        let frame = self.calculate_frame(frame);
-        let half_frame = self.half_frame * scale;
-        let destination = hotspot - half_frame;
+    /// Queues a frame of the sprite atlas for drawing, multiplying its
+    /// colors by a tint. Frames which do not exist are not drawn.
+    pub fn queue_draw_tinted(
+        &mut self,
+        frame: u32,
+        hotspot: Vec2,
+        scale: Vec2,
+        rotation: f32,
+        tint: Color,
+    ) -> GameResult {
+        let frame = match self.frames.get(frame as usize) {
+            Some(frame) => *frame,
+            None => return Ok(()),
+        };
+        let pivot = Mat2::from_angle(rotation) * (frame.pivot * scale);
+        let destination = hotspot - pivot;
         let params = DrawParam::default()
-            .src(frame)
+            .src(frame.src)
             .scale(scale)
-            .dest(destination);
+            .rotation(rotation)
+            .dest(destination)
+            .color(tint);
         self.batch.add(params);
         Ok(())
     }
 
     /// Draws a frame of the sprite atlas, immediately.
     ///
-    /// Requires a drawing context, the number of the frame, the center position
+    /// Requires a drawing context, the number of the frame, the position
     /// of the sprite on screen, and a scale factor related to each axis.
     pub fn immediate_draw(
         &self,
@@ -76,16 +106,30 @@ impl SpriteAtlas {
         hotspot: Vec2,
         scale: Vec2,
     ) -> GameResult {
-        let frame = self.calculate_frame(frame);
-        let half_frame = self.half_frame * scale;
-        let destination = hotspot - half_frame;
+        let frame = match self.frames.get(frame as usize) {
+            Some(frame) => *frame,
+            None => return Ok(()),
+        };
+        let destination = hotspot - frame.pivot * scale;
         let params = DrawParam::default()
-            .src(frame)
+            .src(frame.src)
             .scale(scale)
             .dest(destination);
         graphics::draw(context, &self.texture, params)
     }
 
+    /// Replaces the image of the sprite atlas, such as a recolored
+    /// version of it. The new image must have the same size.
+    pub fn set_texture(&mut self, texture: Image) {
+        self.batch.set_image(texture.clone());
+        self.texture = texture;
+    }
+
+    /// Gets the image of the sprite atlas.
+    pub fn texture(&self) -> &Image {
+        &self.texture
+    }
+
     /// Clears the frame queue. Should be called once
     /// at the beginning of each frame.
     pub fn clear(&mut self) {
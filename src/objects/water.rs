@@ -0,0 +1,89 @@
+use super::general::Position;
+use super::player::{PlayerConstants, PlayerSpeed, PlayerState};
+use ggez::graphics::{self, Color, DrawMode, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+use glam::*;
+use legion::*;
+
+/// Highest upwards speed a player can get when leaving the water.
+const MAX_EXIT_SPEED: f32 = -16.0;
+
+/// Represents the water of a level, which fills everything below
+/// a certain height.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Water {
+    /// Vertical position of the water surface, in pixels.
+    pub height: f32,
+    /// Tint drawn over everything below the surface.
+    pub color: Color,
+}
+
+impl Water {
+    /// Updates whether players are underwater, switching their
+    /// constants between the regular and the underwater ones.
+    ///
+    /// Entering the water halves the horizontal speed and quarters
+    /// the vertical speed, and leaving it doubles the vertical speed.
+    ///
+    /// Returns the points of the surface where players crossed it, so
+    /// that splashes can be shown there.
+    pub fn update(&self, world: &mut World, constants: &PlayerConstants) -> Vec<Vec2> {
+        let mut splashes = Vec::new();
+        let mut query = <(
+            &mut PlayerState,
+            &mut PlayerConstants,
+            &Position,
+            &mut PlayerSpeed,
+        )>::query();
+        for (state, player_constants, position, speed) in query.iter_mut(world) {
+            let underwater = position.0.y > self.height;
+            if underwater == state.underwater {
+                continue;
+            }
+            state.underwater = underwater;
+            if !state.is_dead() {
+                splashes.push(glam::vec2(position.0.x, self.height));
+            }
+            if underwater {
+                *player_constants = constants.underwater();
+                speed.xsp /= 2.0;
+                speed.gsp /= 2.0;
+                speed.ysp /= 4.0;
+            } else {
+                *player_constants = *constants;
+                if !state.is_dead() {
+                    speed.ysp = (speed.ysp * 2.0).max(MAX_EXIT_SPEED);
+                }
+            }
+        }
+        splashes
+    }
+
+    /// Draws the water tint over the visible area below the surface.
+    pub fn draw<F>(&self, context: &mut Context, transform: F) -> GameResult
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        let screen = graphics::screen_coordinates(context);
+        let surface = transform(glam::vec2(0.0, self.height)).y.round();
+        let top = surface.max(0.0);
+        if top >= screen.h {
+            return Ok(());
+        }
+        let mut builder = MeshBuilder::new();
+        builder.rectangle(
+            DrawMode::fill(),
+            Rect::new(0.0, top, screen.w, screen.h - top),
+            self.color,
+        )?;
+        if surface >= 0.0 {
+            builder.line(
+                &[glam::vec2(0.0, surface), glam::vec2(screen.w, surface)],
+                1.0,
+                Color::new(1.0, 1.0, 1.0, 0.6),
+            )?;
+        }
+        let mesh = builder.build(context)?;
+        graphics::draw(context, &mesh, (Vec2::ZERO, 0.0, Color::WHITE))
+    }
+}
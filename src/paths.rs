@@ -0,0 +1,23 @@
+/// Resolves a path relative to the directory of another file.
+pub fn resolve(base: &str, relative: &str) -> String {
+    let mut parts: Vec<&str> = base.split('/').collect();
+    parts.pop();
+    if relative.starts_with('/') {
+        parts.clear();
+    }
+    for part in relative.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    let path = parts.join("/");
+    if base.starts_with('/') && !path.starts_with('/') {
+        format!("/{}", path)
+    } else {
+        path
+    }
+}
@@ -0,0 +1,896 @@
+use crate::input::{Input, InputButton};
+use crate::level::collision::{CollisionLayer, CollisionMap, TILE_SIZE};
+use crate::level::format::{
+    ChunkData, LayoutData, LevelData, MaskData, ObjectData, ObjectKindData, TileRef, CHUNK_SIZE,
+    CHUNK_TILES,
+};
+use crate::level::manifest::{ActId, ManifestData, MANIFEST_PATH};
+use crate::level::tiled::TiledFormat;
+use crate::level::tilemap::{TileAnimations, TilemapRenderer};
+use crate::objects::general::{Position, Priority};
+use crate::objects::object_manager::ObjectPlacement;
+use crate::objects::path_swapper::SwapperOrientation;
+use crate::screen_systems::levelscreen::debug_mode::{catalogue, CatalogueEntry};
+use crate::screen_systems::{Navigation, TransitionEffect, Transitions, WipeDirection};
+use ggez::event::MouseButton;
+use ggez::graphics::{
+    self, Color, DrawMode, Image, MeshBuilder, PxScale, Rect, Text, TextFragment,
+};
+use ggez::input::keyboard::KeyCode;
+use ggez::{Context, GameResult};
+use glam::*;
+use std::env;
+use std::path::PathBuf;
+
+/// Gets the path on disk a level is saved to, inside of the
+/// resources directory the game is running from.
+///
+/// Tiled maps are not overwritten: the level is saved next to the
+/// map, as a level file with the same name.
+fn level_file(level_path: &str) -> PathBuf {
+    let mut path = match env::var("CARGO_MANIFEST_DIR") {
+        Ok(manifest_dir) => PathBuf::from(manifest_dir).join("resources"),
+        Err(_) => PathBuf::from("./resources"),
+    };
+    path.push(level_path.trim_start_matches('/'));
+    if TiledFormat::from_path(level_path).is_some() {
+        path.set_extension("json");
+    }
+    path
+}
+
+/// Speed of the view when scrolled with the arrow keys, in pixels
+/// per frame. Holding shift multiplies it by four.
+const SCROLL_SPEED: f32 = 8.0;
+
+/// Amount of frames a status message stays on screen.
+const STATUS_FRAMES: u32 = 180;
+
+/// Describes the tool currently used on the level editor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tool {
+    /// Paints single tiles on the current plane.
+    Tile,
+    /// Places whole chunks on the current plane.
+    Chunk,
+    /// Places, moves and deletes objects.
+    Object,
+    /// Paints the collision masks of foreground tiles.
+    Collision,
+    /// Sets the camera bounds of the level.
+    Bounds,
+    /// Sets the start position of the level.
+    Start,
+}
+
+impl Tool {
+    fn name(&self) -> &'static str {
+        match self {
+            Tool::Tile => "TILE",
+            Tool::Chunk => "CHUNK",
+            Tool::Object => "OBJECT",
+            Tool::Collision => "COLLISION",
+            Tool::Bounds => "BOUNDS",
+            Tool::Start => "START",
+        }
+    }
+}
+
+/// Describes the plane of the level being edited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Plane {
+    Foreground,
+    Background,
+}
+
+fn layout(level: &LevelData, plane: Plane) -> &LayoutData {
+    match plane {
+        Plane::Foreground => &level.foreground,
+        Plane::Background => &level.background,
+    }
+}
+
+fn layout_mut(level: &mut LevelData, plane: Plane) -> &mut LayoutData {
+    match plane {
+        Plane::Foreground => &mut level.foreground,
+        Plane::Background => &mut level.background,
+    }
+}
+
+/// Counts how many positions of both layouts use a chunk.
+fn chunk_users(level: &LevelData, chunk: u16) -> usize {
+    level
+        .foreground
+        .chunks
+        .iter()
+        .chain(level.background.chunks.iter())
+        .filter(|&&c| c == Some(chunk))
+        .count()
+}
+
+/// Counts how many tiles use a height mask, on either layer.
+fn mask_users(level: &LevelData, mask: u16) -> usize {
+    level
+        .tiles
+        .iter()
+        .flat_map(|tile| [tile.collision_a, tile.collision_b])
+        .filter(|&m| m == Some(mask))
+        .count()
+}
+
+/// Calculates the area covered by an object on the editor.
+fn object_size(kind: &ObjectKindData) -> Vec2 {
+    match kind {
+        ObjectKindData::Spikes { width, height, .. } => glam::vec2(*width, *height),
+        ObjectKindData::Hazard { width, height, .. } => glam::vec2(*width, *height),
+        ObjectKindData::Checkpoint { .. } => glam::vec2(16.0, 48.0),
+        ObjectKindData::Signpost => glam::vec2(48.0, 48.0),
+        ObjectKindData::PathSwapper {
+            orientation,
+            length,
+            ..
+        } => match orientation {
+            SwapperOrientation::Vertical => glam::vec2(2.0, *length),
+            SwapperOrientation::Horizontal => glam::vec2(*length, 2.0),
+        },
+        ObjectKindData::ArenaLock { width, height, .. } => glam::vec2(*width, *height),
+    }
+}
+
+/// Grows a layout so that it has a chunk position at least at a
+/// certain column and row. New positions are empty.
+fn grow_layout(layout: &mut LayoutData, x: usize, y: usize) {
+    let width = layout.width.max(x + 1);
+    let height = layout.height.max(y + 1);
+    if (width, height) == (layout.width, layout.height) {
+        return;
+    }
+    let mut chunks = vec![None; width * height];
+    for row in 0..layout.height {
+        for column in 0..layout.width {
+            chunks[row * width + column] = layout.chunks[row * layout.width + column];
+        }
+    }
+    *layout = LayoutData {
+        width,
+        height,
+        chunks,
+    };
+}
+
+/// Cycles an index through a certain amount of entries.
+fn cycle(index: usize, step: i32, count: usize) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    (index as i64 + step as i64).rem_euclid(count as i64) as usize
+}
+
+/// Defines the state for the level editor screen system.
+///
+/// The editor works directly on level data, which is saved on the
+/// level file format. The edited level is the one of an act on the
+/// manifest, and Page Up and Page Down switch to the previous and
+/// next acts, dropping unsaved changes. Tools are picked with the
+/// number keys:
+///
+/// 1. Tile: left click paints the selected tile, right click erases.
+///    F and V flip the selected tile, and P toggles its priority.
+/// 2. Chunk: left click places the selected chunk, right click clears.
+/// 3. Object: left click places or drags objects, right click deletes.
+/// 4. Collision: left click raises a mask column up to the cursor,
+///    right click clears it. L switches layers, Q and E rotate the
+///    surface angle and R removes it.
+/// 5. Bounds: left click sets the top left corner of the camera
+///    bounds, right click sets the bottom right corner.
+/// 6. Start: left click sets the start position.
+///
+/// The mouse wheel or the bracket keys change the selection, Tab
+/// switches planes, arrows or the middle button scroll the view, F2
+/// saves over the level file of the act on the resources directory
+/// and F5 play tests from the cursor position. Placing tiles or
+/// chunks past the right or bottom edges of a plane grows it.
+/// Checkpoints are renumbered after their order on the level as
+/// they are moved.
+pub struct LevelEditorSystem {
+    manifest: Option<ManifestData>,
+    act: ActId,
+    level: Option<LevelData>,
+    terrain: Option<CollisionMap>,
+    foreground: Option<TilemapRenderer>,
+    background: Option<TilemapRenderer>,
+    animations: Option<TileAnimations>,
+    screen: Rect,
+    view: Vec2,
+    mouse: Vec2,
+    tool: Tool,
+    plane: Plane,
+    tile: TileRef,
+    chunk: u16,
+    catalogue: Vec<CatalogueEntry>,
+    selected: usize,
+    dragging: Option<(usize, Vec2)>,
+    layer: CollisionLayer,
+    status: Option<(String, u32)>,
+    play_test: Option<(LevelData, Position)>,
+}
+
+impl LevelEditorSystem {
+    /// Creates a new level editor screen system.
+    pub fn new() -> Self {
+        Self {
+            manifest: None,
+            act: ActId::default(),
+            level: None,
+            terrain: None,
+            foreground: None,
+            background: None,
+            animations: None,
+            screen: Rect::default(),
+            view: Vec2::ZERO,
+            mouse: Vec2::ZERO,
+            tool: Tool::Tile,
+            plane: Plane::Foreground,
+            tile: TileRef::default(),
+            chunk: 0,
+            catalogue: catalogue(),
+            selected: 0,
+            dragging: None,
+            layer: CollisionLayer::A,
+            status: None,
+            play_test: None,
+        }
+    }
+
+    /// Sets up the level editor, loading the level of the first act.
+    pub fn setup(&mut self, context: &mut Context) -> GameResult {
+        self.screen = graphics::screen_coordinates(context);
+        self.manifest = Some(ManifestData::load(context, MANIFEST_PATH)?);
+        self.load_act(context, ActId::default())
+    }
+
+    /// Path to the level file of the edited act.
+    fn level_path(&self) -> Option<&str> {
+        self.manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get(self.act))
+            .map(|(_, act)| act.level.as_str())
+    }
+
+    /// Loads the level of an act of the manifest for editing.
+    fn load_act(&mut self, context: &mut Context, id: ActId) -> GameResult {
+        let path = match self.manifest.as_ref().and_then(|manifest| manifest.get(id)) {
+            Some((_, act)) => act.level.clone(),
+            None => return Ok(()),
+        };
+        let level = LevelData::load(context, &path)?;
+        let tileset = Image::new(context, &level.tileset)?;
+        self.foreground = Some(TilemapRenderer::new(tileset.clone()));
+        self.background = Some(TilemapRenderer::new(tileset));
+        self.animations = Some(TileAnimations::new(&level));
+        self.terrain = Some(level.collision_map());
+        self.view = (level.start_position().0 - glam::vec2(self.screen.w, self.screen.h) / 2.0)
+            .max(Vec2::ZERO)
+            .round();
+        self.level = Some(level);
+        self.act = id;
+        self.dragging = None;
+        self.queue();
+        Ok(())
+    }
+
+    /// Takes the level and start position requested for play testing,
+    /// if play testing was requested on the last update.
+    pub fn take_play_test(&mut self) -> Option<(LevelData, Position)> {
+        self.play_test.take()
+    }
+
+    /// Shows a status message for a while.
+    fn set_status(&mut self, message: String) {
+        self.status = Some((message, STATUS_FRAMES));
+    }
+
+    /// Transforms a point on the level into a point on screen.
+    fn transform(&self, vertex: Vec2) -> Vec2 {
+        vertex - self.view
+    }
+
+    /// Area of the level visible on screen.
+    fn view_rect(&self) -> Rect {
+        Rect::new(self.view.x, self.view.y, self.screen.w, self.screen.h)
+    }
+
+    /// Tile position under a point of the level.
+    fn tile_position(point: Vec2) -> Option<(usize, usize)> {
+        if (point.x < 0.0) || (point.y < 0.0) {
+            return None;
+        }
+        let tile = TILE_SIZE as f32;
+        Some(((point.x / tile) as usize, (point.y / tile) as usize))
+    }
+
+    /// Queues the visible tiles of both planes.
+    fn queue(&mut self) {
+        let view = self.view_rect();
+        let offset = self.view;
+        let (level, animations) = match (&self.level, &self.animations) {
+            (Some(level), Some(animations)) => (level, animations),
+            _ => return,
+        };
+        if let Some(background) = self.background.as_mut() {
+            background.queue(level, &level.background, animations, view, |v| v - offset);
+        }
+        if let Some(foreground) = self.foreground.as_mut() {
+            foreground.queue(level, &level.foreground, animations, view, |v| v - offset);
+        }
+    }
+
+    /// Sets a tile on the current plane, returning whether anything
+    /// changed.
+    ///
+    /// Chunks used on more than one position are copied before being
+    /// changed, so that painting only affects the position under the
+    /// cursor. Empty positions receive a new empty chunk, and the
+    /// plane grows to fit tiles painted outside of it.
+    fn paint_tile(
+        level: &mut LevelData,
+        plane: Plane,
+        x: usize,
+        y: usize,
+        tile: Option<TileRef>,
+    ) -> bool {
+        let (chunk_x, chunk_y) = (x / CHUNK_TILES, y / CHUNK_TILES);
+        if level.tile_at(layout(level, plane), x, y) == tile {
+            return false;
+        }
+        grow_layout(layout_mut(level, plane), chunk_x, chunk_y);
+        let target = layout(level, plane);
+
+        let slot = chunk_y * target.width + chunk_x;
+        let chunk = match target.chunks[slot] {
+            Some(chunk) if chunk_users(level, chunk) == 1 => chunk,
+            existing => {
+                let tiles = existing
+                    .map(|chunk| level.chunks[chunk as usize].tiles.clone())
+                    .unwrap_or_else(|| vec![None; CHUNK_TILES * CHUNK_TILES]);
+                level.chunks.push(ChunkData { tiles });
+                let chunk = (level.chunks.len() - 1) as u16;
+                layout_mut(level, plane).chunks[slot] = Some(chunk);
+                chunk
+            }
+        };
+        level.chunks[chunk as usize].tiles[(y % CHUNK_TILES) * CHUNK_TILES + (x % CHUNK_TILES)] =
+            tile;
+        true
+    }
+
+    /// Gets the height mask of a tile on a layer for editing, returning
+    /// its index.
+    ///
+    /// Masks used by other tiles are copied first, and tiles without a
+    /// mask receive an empty one if `create` is set.
+    fn edit_mask(
+        level: &mut LevelData,
+        tile: u16,
+        layer: CollisionLayer,
+        create: bool,
+    ) -> Option<u16> {
+        let data = &level.tiles[tile as usize];
+        let current = match layer {
+            CollisionLayer::A => data.collision_a,
+            CollisionLayer::B => data.collision_b,
+        };
+        let mask = match current {
+            Some(mask) if mask_users(level, mask) == 1 => return Some(mask),
+            Some(mask) => level.masks[mask as usize].clone(),
+            None if create => MaskData {
+                heights: [0; TILE_SIZE],
+                angle: None,
+            },
+            None => return None,
+        };
+        level.masks.push(mask);
+        let mask = (level.masks.len() - 1) as u16;
+        let data = &mut level.tiles[tile as usize];
+        match layer {
+            CollisionLayer::A => data.collision_a = Some(mask),
+            CollisionLayer::B => data.collision_b = Some(mask),
+        }
+        Some(mask)
+    }
+
+    /// Index of the topmost object under a point of the level.
+    fn object_at(level: &LevelData, point: Vec2) -> Option<usize> {
+        level.objects.iter().rposition(|object| {
+            let half = object_size(&object.kind).max(Vec2::splat(8.0)) / 2.0;
+            let distance = (point - glam::vec2(object.x, object.y)).abs();
+            (distance.x <= half.x) && (distance.y <= half.y)
+        })
+    }
+
+    /// Updates the view, scrolled with the arrow keys or by dragging
+    /// with the middle mouse button.
+    fn update_view(&mut self, input: &Input) {
+        let (x, y) = input.mouse_position();
+        let mouse = glam::vec2(x, y);
+        if input.mouse_pressing(MouseButton::Middle) {
+            self.view -= mouse - self.mouse;
+        }
+        self.mouse = mouse;
+
+        let speed = if input.key_pressing(KeyCode::LShift) || input.key_pressing(KeyCode::RShift) {
+            SCROLL_SPEED * 4.0
+        } else {
+            SCROLL_SPEED
+        };
+        let axis = |negative, positive| {
+            (input.pressing(positive) as i32 - input.pressing(negative) as i32) as f32
+        };
+        self.view += glam::vec2(
+            axis(InputButton::Left, InputButton::Right),
+            axis(InputButton::Up, InputButton::Down),
+        ) * speed;
+        self.view = self.view.max(-Vec2::splat(CHUNK_SIZE as f32)).round();
+    }
+
+    /// Applies the current tool, returning whether the level terrain
+    /// changed.
+    fn update_tool(&mut self, input: &Input, point: Vec2) -> bool {
+        let step = if input.wheel() > 0.0 || input.key_pressed(KeyCode::RBracket) {
+            1
+        } else if input.wheel() < 0.0 || input.key_pressed(KeyCode::LBracket) {
+            -1
+        } else {
+            0
+        };
+        let left = input.mouse_pressing(MouseButton::Left);
+        let right = input.mouse_pressing(MouseButton::Right);
+        let plane = self.plane;
+        let level = match self.level.as_mut() {
+            Some(level) => level,
+            None => return false,
+        };
+
+        match self.tool {
+            Tool::Tile => {
+                self.tile.tile = cycle(self.tile.tile as usize, step, level.tiles.len()) as u16;
+                if input.key_pressed(KeyCode::F) {
+                    self.tile.flip_x = !self.tile.flip_x;
+                }
+                if input.key_pressed(KeyCode::V) {
+                    self.tile.flip_y = !self.tile.flip_y;
+                }
+                if input.key_pressed(KeyCode::P) {
+                    self.tile.high_priority = !self.tile.high_priority;
+                }
+                let tile = match (left, right) {
+                    (true, _) if !level.tiles.is_empty() => Some(self.tile),
+                    (false, true) => None,
+                    _ => return false,
+                };
+                match Self::tile_position(point) {
+                    Some((x, y)) => Self::paint_tile(level, plane, x, y, tile),
+                    None => false,
+                }
+            }
+            Tool::Chunk => {
+                self.chunk = cycle(self.chunk as usize, step, level.chunks.len()) as u16;
+                let chunk = match (left, right) {
+                    (true, _) if !level.chunks.is_empty() => Some(self.chunk),
+                    (false, true) => None,
+                    _ => return false,
+                };
+                let (x, y) = match Self::tile_position(point) {
+                    Some((x, y)) => (x / CHUNK_TILES, y / CHUNK_TILES),
+                    None => return false,
+                };
+                let target = layout_mut(level, plane);
+                if chunk.is_some() {
+                    grow_layout(target, x, y);
+                } else if (x >= target.width) || (y >= target.height) {
+                    return false;
+                }
+                let slot = y * target.width + x;
+                let changed = target.chunks[slot] != chunk;
+                target.chunks[slot] = chunk;
+                changed
+            }
+            Tool::Object => {
+                self.selected = cycle(self.selected, step, self.catalogue.len());
+                if input.mouse_pressed(MouseButton::Left) {
+                    let index = match Self::object_at(level, point) {
+                        Some(index) => index,
+                        None => {
+                            level
+                                .objects
+                                .push(ObjectData::from_placement(&ObjectPlacement {
+                                    kind: self.catalogue[self.selected].kind,
+                                    position: Position::wrap(point.round()),
+                                }));
+                            level.objects.len() - 1
+                        }
+                    };
+                    let object = &level.objects[index];
+                    self.dragging = Some((index, glam::vec2(object.x, object.y) - point));
+                }
+                if !left {
+                    self.dragging = None;
+                }
+                if let Some((index, offset)) = self.dragging {
+                    let position = (point + offset).round();
+                    let object = &mut level.objects[index];
+                    object.x = position.x;
+                    object.y = position.y;
+                    // Checkpoints are numbered after their order on
+                    // the level, which may change as they are moved
+                    level.renumber_checkpoints();
+                } else if input.mouse_pressed(MouseButton::Right) {
+                    if let Some(index) = Self::object_at(level, point) {
+                        level.objects.remove(index);
+                        level.renumber_checkpoints();
+                    }
+                }
+                false
+            }
+            Tool::Collision => {
+                if input.key_pressed(KeyCode::L) {
+                    self.layer = match self.layer {
+                        CollisionLayer::A => CollisionLayer::B,
+                        CollisionLayer::B => CollisionLayer::A,
+                    };
+                }
+                let (x, y) = match Self::tile_position(point) {
+                    Some(position) => position,
+                    None => return false,
+                };
+                let tile_ref = match level.tile_at(&level.foreground, x, y) {
+                    Some(tile_ref) => tile_ref,
+                    None => return false,
+                };
+
+                let mut changed = false;
+                let rotation =
+                    input.key_pressed(KeyCode::E) as i32 - input.key_pressed(KeyCode::Q) as i32;
+                if rotation != 0 || input.key_pressed(KeyCode::R) {
+                    if let Some(mask) = Self::edit_mask(level, tile_ref.tile, self.layer, false) {
+                        let mask = &mut level.masks[mask as usize];
+                        mask.angle = if rotation != 0 {
+                            let angle = mask.angle.unwrap_or(0.0) + rotation as f32;
+                            Some(angle.rem_euclid(360.0))
+                        } else {
+                            None
+                        };
+                        changed = true;
+                    }
+                }
+
+                if left || right {
+                    // Pixel under the cursor, on the unflipped mask
+                    let mut local = (point / TILE_SIZE as f32).fract() * TILE_SIZE as f32;
+                    if tile_ref.flip_x {
+                        local.x = TILE_SIZE as f32 - 1.0 - local.x;
+                    }
+                    if tile_ref.flip_y {
+                        local.y = TILE_SIZE as f32 - 1.0 - local.y;
+                    }
+                    let column = (local.x as usize).min(TILE_SIZE - 1);
+                    let height = if left {
+                        TILE_SIZE as u8 - (local.y as u8).min(TILE_SIZE as u8 - 1)
+                    } else {
+                        0
+                    };
+                    if let Some(mask) = Self::edit_mask(level, tile_ref.tile, self.layer, left) {
+                        let heights = &mut level.masks[mask as usize].heights;
+                        changed |= heights[column] != height;
+                        heights[column] = height;
+                    }
+                }
+                changed
+            }
+            Tool::Bounds => {
+                let point = (point / TILE_SIZE as f32).round() * TILE_SIZE as f32;
+                if left {
+                    level.camera_bounds.min = [point.x, point.y];
+                }
+                if right {
+                    level.camera_bounds.max = [point.x, point.y];
+                }
+                false
+            }
+            Tool::Start => {
+                if left {
+                    level.start = [point.x.round(), point.y.round()];
+                }
+                false
+            }
+        }
+    }
+
+    /// Updates the level editor screen system.
+    pub fn update(
+        &mut self,
+        context: &mut Context,
+        transitions: &mut Transitions,
+        input: &Input,
+    ) -> GameResult {
+        if let Some((_, frames)) = self.status.as_mut() {
+            *frames = frames.saturating_sub(1);
+            if *frames == 0 {
+                self.status = None;
+            }
+        }
+
+        let switch = match &self.manifest {
+            Some(manifest) if input.key_pressed(KeyCode::PageUp) => manifest.previous(self.act),
+            Some(manifest) if input.key_pressed(KeyCode::PageDown) => manifest.next(self.act),
+            _ => None,
+        };
+        if let Some(id) = switch {
+            if let Err(e) = self.load_act(context, id) {
+                self.set_status(format!("COULD NOT LOAD: {}", e));
+            }
+        }
+
+        self.update_view(input);
+        let point = self.view + self.mouse;
+
+        let tools = [
+            (KeyCode::Key1, Tool::Tile),
+            (KeyCode::Key2, Tool::Chunk),
+            (KeyCode::Key3, Tool::Object),
+            (KeyCode::Key4, Tool::Collision),
+            (KeyCode::Key5, Tool::Bounds),
+            (KeyCode::Key6, Tool::Start),
+        ];
+        if let Some(&(_, tool)) = tools.iter().find(|(key, _)| input.key_pressed(*key)) {
+            self.tool = tool;
+            self.dragging = None;
+        }
+        if input.key_pressed(KeyCode::Tab) {
+            self.plane = match self.plane {
+                Plane::Foreground => Plane::Background,
+                Plane::Background => Plane::Foreground,
+            };
+        }
+
+        if self.update_tool(input, point) {
+            self.terrain = self.level.as_ref().map(LevelData::collision_map);
+        }
+
+        if input.key_pressed(KeyCode::F2) {
+            if let (Some(level), Some(level_path)) = (&self.level, self.level_path()) {
+                let path = level_file(level_path);
+                match level.validate(&level.name).and_then(|_| level.save(&path)) {
+                    Ok(_) => self.set_status(format!("SAVED {}", path.display())),
+                    Err(e) => self.set_status(format!("COULD NOT SAVE: {}", e)),
+                }
+            }
+        }
+
+        if input.key_pressed(KeyCode::F5) {
+            if let Some(level) = &self.level {
+                self.play_test = Some((level.clone(), Position::wrap(point.round())));
+                self.dragging = None;
+                transitions.request(
+                    Navigation::LevelScreen,
+                    TransitionEffect::Fade(Color::BLACK),
+                    12,
+                );
+                return Ok(());
+            }
+        }
+
+        if input.pressed(InputButton::Back) {
+            self.dragging = None;
+            transitions.request(
+                Navigation::TitleScreen,
+                TransitionEffect::Wipe(WipeDirection::Right),
+                20,
+            );
+        }
+
+        if let Some(animations) = self.animations.as_mut() {
+            animations.update();
+        }
+        self.queue();
+        Ok(())
+    }
+
+    /// Draws the editor overlays: chunk grid, objects, start position,
+    /// camera bounds and the highlight under the cursor.
+    fn draw_overlays(&self, context: &mut Context, level: &LevelData) -> GameResult {
+        let view = self.view_rect();
+        let mut builder = MeshBuilder::new();
+
+        // Chunk grid of the current plane
+        let target = layout(level, self.plane);
+        let grid = Color::new(1.0, 1.0, 1.0, 0.2);
+        let size = glam::vec2(
+            (target.width * CHUNK_SIZE) as f32,
+            (target.height * CHUNK_SIZE) as f32,
+        );
+        let chunk = CHUNK_SIZE as f32;
+        let first = (view.x / chunk).floor().max(0.0) as usize;
+        let last = ((view.right() / chunk).ceil().max(0.0) as usize).min(target.width);
+        for x in first..=last {
+            let x = (x * CHUNK_SIZE) as f32;
+            builder.line(
+                &[
+                    self.transform(glam::vec2(x, 0.0)),
+                    self.transform(glam::vec2(x, size.y)),
+                ],
+                1.0,
+                grid,
+            )?;
+        }
+        let top = (view.y / chunk).floor().max(0.0) as usize;
+        let bottom = ((view.bottom() / chunk).ceil().max(0.0) as usize).min(target.height);
+        for y in top..=bottom {
+            let y = (y * CHUNK_SIZE) as f32;
+            builder.line(
+                &[
+                    self.transform(glam::vec2(0.0, y)),
+                    self.transform(glam::vec2(size.x, y)),
+                ],
+                1.0,
+                grid,
+            )?;
+        }
+
+        // Objects
+        for (i, object) in level.objects.iter().enumerate() {
+            let size = object_size(&object.kind).max(Vec2::splat(2.0));
+            let corner = self.transform(glam::vec2(object.x, object.y) - size / 2.0);
+            let color = if self.dragging.map(|(index, _)| index) == Some(i) {
+                Color::new(1.0, 1.0, 0.0, 1.0)
+            } else {
+                Color::new(1.0, 0.5, 0.0, 0.8)
+            };
+            builder.rectangle(
+                DrawMode::stroke(1.0),
+                Rect::new(corner.x, corner.y, size.x, size.y),
+                color,
+            )?;
+        }
+
+        // Start position
+        let start = self.transform(level.start_position().0);
+        let green = Color::new(0.0, 1.0, 0.0, 1.0);
+        builder
+            .line(
+                &[start - glam::vec2(8.0, 0.0), start + glam::vec2(8.0, 0.0)],
+                1.0,
+                green,
+            )?
+            .line(
+                &[start - glam::vec2(0.0, 8.0), start + glam::vec2(0.0, 8.0)],
+                1.0,
+                green,
+            )?;
+
+        // Camera regions
+        for region in level.camera_regions() {
+            let corner = self.transform(glam::vec2(region.area.x, region.area.y));
+            builder.rectangle(
+                DrawMode::stroke(1.0),
+                Rect::new(corner.x, corner.y, region.area.w, region.area.h),
+                Color::new(0.0, 1.0, 1.0, 0.6),
+            )?;
+        }
+
+        // Camera bounds
+        let bounds = level.camera_bounds();
+        let min = self.transform(bounds.min);
+        let max = self.transform(bounds.max);
+        builder.rectangle(
+            DrawMode::stroke(2.0),
+            Rect::new(min.x, min.y, max.x - min.x, max.y - min.y),
+            Color::new(1.0, 0.0, 1.0, 0.8),
+        )?;
+
+        // Area under the cursor
+        let point = self.view + self.mouse;
+        let cursor = match self.tool {
+            Tool::Tile | Tool::Collision => Some(TILE_SIZE as f32),
+            Tool::Chunk => Some(chunk),
+            _ => None,
+        };
+        if let Some(cell) = cursor.filter(|_| (point.x >= 0.0) && (point.y >= 0.0)) {
+            let corner = self.transform((point / cell).floor() * cell);
+            builder.rectangle(
+                DrawMode::stroke(1.0),
+                Rect::new(corner.x, corner.y, cell, cell),
+                Color::new(1.0, 1.0, 0.0, 1.0),
+            )?;
+        }
+
+        let mesh = builder.build(context)?;
+        graphics::draw(context, &mesh, (Vec2::ZERO, 0.0, Color::WHITE))
+    }
+
+    /// Draws the level editor screen.
+    pub fn draw(&self, context: &mut Context) -> GameResult {
+        let level = match &self.level {
+            Some(level) => level,
+            None => return Ok(()),
+        };
+
+        for priority in [Priority::Low, Priority::High] {
+            if let Some(background) = &self.background {
+                background.draw(context, priority)?;
+            }
+            if let Some(foreground) = &self.foreground {
+                foreground.draw(context, priority)?;
+            }
+        }
+        if self.tool == Tool::Collision {
+            if let Some(terrain) = &self.terrain {
+                terrain.debug_draw(context, self.layer, self.view_rect(), |v| self.transform(v))?;
+            }
+        }
+        self.draw_overlays(context, level)?;
+
+        // Status text
+        let point = self.view + self.mouse;
+        let selection = match self.tool {
+            Tool::Tile => format!(
+                "TILE {}{}{}{}",
+                self.tile.tile,
+                if self.tile.flip_x { " FLIP X" } else { "" },
+                if self.tile.flip_y { " FLIP Y" } else { "" },
+                if self.tile.high_priority { " HIGH" } else { "" }
+            ),
+            Tool::Chunk => format!("CHUNK {}", self.chunk),
+            Tool::Object => self.catalogue[self.selected].name.to_string(),
+            Tool::Collision => {
+                let angle = Self::tile_position(point)
+                    .and_then(|(x, y)| level.tile_at(&level.foreground, x, y))
+                    .and_then(|tile_ref| {
+                        let tile = &level.tiles[tile_ref.tile as usize];
+                        match self.layer {
+                            CollisionLayer::A => tile.collision_a,
+                            CollisionLayer::B => tile.collision_b,
+                        }
+                    })
+                    .and_then(|mask| level.masks[mask as usize].angle);
+                match angle {
+                    Some(angle) => format!("LAYER {:?} ANGLE {:.2}", self.layer, angle),
+                    None => format!("LAYER {:?} NO ANGLE", self.layer),
+                }
+            }
+            Tool::Bounds => {
+                let bounds = &level.camera_bounds;
+                format!(
+                    "MIN {:?} MAX {:?}",
+                    (bounds.min[0] as i32, bounds.min[1] as i32),
+                    (bounds.max[0] as i32, bounds.max[1] as i32)
+                )
+            }
+            Tool::Start => format!("START {} {}", level.start[0] as i32, level.start[1] as i32),
+        };
+        let mut hud_text = format!(
+            "LEVEL EDITOR  {}  ZONE {} ACT {}\n\
+             TOOL {}  PLANE {:?}\n\
+             {}\n\
+             X {:>5} Y {:>5}",
+            level.name,
+            self.act.zone + 1,
+            self.act.act + 1,
+            self.tool.name(),
+            self.plane,
+            selection,
+            point.x as i32,
+            point.y as i32
+        );
+        if let Some((message, _)) = &self.status {
+            hud_text = format!("{}\n{}", hud_text, message);
+        }
+        let text = Text::new(
+            TextFragment::new(hud_text)
+                .color(Color::new(1.0, 1.0, 0.0, 1.0))
+                .scale(PxScale::from(8.0)),
+        );
+        graphics::queue_text(context, &text, glam::vec2(5.0, 5.0), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use crate::font::bitmap_font::BitmapFont;
+use crate::font::layout::{Align, TextStyle};
+use crate::input::{Input, InputButton};
+use crate::objects::player::PlayerState;
+use crate::objects::signpost::Signpost;
+use ggez::graphics::{Color, Rect};
+use ggez::GameResult;
+use legion::*;
+
+/// Amount of frames the player runs before the tally starts.
+const RUN_OFF_FRAMES: u32 = 90;
+
+/// Amount of frames to wait after the tally, before leaving the act.
+const FINISHED_FRAMES: u32 = 180;
+
+/// Amount of points moved from each bonus to the score per frame.
+const TALLY_STEP: u32 = 100;
+
+/// Path to the font of the act clear overlay.
+pub const FONT_PATH: &str = "/fonts/default.json";
+
+/// Scale factor of the act clear title.
+const TITLE_SCALE: f32 = 2.0;
+
+const TALLY_COLOR: Color = Color::new(1.0, 1.0, 0.0, 1.0);
+
+/// Describes the stages of the act clear sequence.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ActClearStage {
+    /// The signpost is spinning, and players still have control.
+    Signpost,
+    /// Player control is locked and players run off screen. Holds
+    /// the amount of elapsed frames.
+    RunOff(u32),
+    /// Bonuses are being added to the score.
+    Tally,
+    /// The tally is over. Holds the amount of elapsed frames.
+    Finished(u32),
+}
+
+/// Defines the state of the act clear sequence, shown as an overlay
+/// on the level screen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActClear {
+    /// Current stage of the sequence.
+    pub stage: ActClearStage,
+    /// Remaining time bonus.
+    pub time_bonus: u32,
+    /// Remaining ring bonus.
+    pub ring_bonus: u32,
+    /// Remaining perfect bonus.
+    pub perfect_bonus: u32,
+    perfect: bool,
+    seamless: bool,
+}
+
+impl ActClear {
+    /// Starts the act clear sequence, given the elapsed level time
+    /// in frames.
+    ///
+    /// On seamless sequences, players keep control and do not run
+    /// off screen, since they carry on into the next act.
+    pub fn new(time: u64, seamless: bool) -> Self {
+        Self {
+            stage: ActClearStage::Signpost,
+            time_bonus: Self::time_bonus(time),
+            ring_bonus: 0,
+            perfect_bonus: 0,
+            perfect: false,
+            seamless,
+        }
+    }
+
+    /// Calculates the time bonus for a certain elapsed level time,
+    /// in frames.
+    pub fn time_bonus(time: u64) -> u32 {
+        match time / 60 {
+            0..=29 => 50000,
+            30..=44 => 10000,
+            45..=59 => 5000,
+            60..=89 => 4000,
+            90..=119 => 3000,
+            120..=179 => 2000,
+            180..=239 => 1000,
+            240..=299 => 500,
+            _ => 0,
+        }
+    }
+
+    /// Updates the act clear sequence, adding bonuses to the score.
+    ///
+    /// The total amount of rings on the level is used to determine
+    /// whether the perfect bonus is granted. Returns `true` when the
+    /// sequence is over.
+    pub fn update(
+        &mut self,
+        world: &mut World,
+        score: &mut u32,
+        total_rings: u32,
+        input: &Input,
+    ) -> bool {
+        self.stage = match self.stage {
+            ActClearStage::Signpost => {
+                if Signpost::all_landed(world) && self.seamless {
+                    ActClearStage::RunOff(RUN_OFF_FRAMES)
+                } else if Signpost::all_landed(world) {
+                    let mut query = <&mut PlayerState>::query();
+                    for state in query.iter_mut(world) {
+                        state.autorun = true;
+                    }
+                    ActClearStage::RunOff(0)
+                } else {
+                    ActClearStage::Signpost
+                }
+            }
+            ActClearStage::RunOff(frames) if frames >= RUN_OFF_FRAMES => {
+                let rings = <&PlayerState>::query()
+                    .iter(world)
+                    .map(|state| state.rings)
+                    .max()
+                    .unwrap_or(0);
+                self.ring_bonus = rings * 100;
+                self.perfect = (total_rings > 0) && (rings >= total_rings);
+                self.perfect_bonus = if self.perfect { 50000 } else { 0 };
+                ActClearStage::Tally
+            }
+            ActClearStage::RunOff(frames) => ActClearStage::RunOff(frames + 1),
+            ActClearStage::Tally => {
+                // Pressing start skips the tally
+                let step = if input.pressed(InputButton::Start) {
+                    u32::MAX
+                } else {
+                    TALLY_STEP
+                };
+                for bonus in [
+                    &mut self.time_bonus,
+                    &mut self.ring_bonus,
+                    &mut self.perfect_bonus,
+                ] {
+                    let amount = (*bonus).min(step);
+                    *bonus -= amount;
+                    *score += amount;
+                }
+
+                if (self.time_bonus + self.ring_bonus + self.perfect_bonus) == 0 {
+                    ActClearStage::Finished(0)
+                } else {
+                    ActClearStage::Tally
+                }
+            }
+            ActClearStage::Finished(frames) => ActClearStage::Finished(frames + 1),
+        };
+
+        self.stage == ActClearStage::Finished(FINISHED_FRAMES)
+    }
+
+    /// Queues the act clear overlay for drawing on a font, centered
+    /// on the screen, replacing anything queued before.
+    pub fn queue(&self, font: &mut BitmapFont, score: u32, screen: Rect) -> GameResult {
+        font.clear();
+        match self.stage {
+            ActClearStage::Tally | ActClearStage::Finished(_) => {}
+            _ => return Ok(()),
+        }
+
+        let mut lines = vec![
+            format!("SCORE         {:>6}", score),
+            format!("TIME BONUS    {:>6}", self.time_bonus),
+            format!("RING BONUS    {:>6}", self.ring_bonus),
+        ];
+        if self.perfect {
+            lines.push(format!("PERFECT BONUS {:>6}", self.perfect_bonus));
+        }
+
+        let title = font.layout(
+            "SONIC HAS PASSED",
+            &TextStyle {
+                align: Align::Center,
+                scale: TITLE_SCALE,
+                ..TextStyle::default()
+            },
+        );
+        let tally = font.layout(
+            &lines.join("\n"),
+            &TextStyle {
+                align: Align::Center,
+                color: TALLY_COLOR,
+                ..TextStyle::default()
+            },
+        );
+
+        let center = glam::vec2(screen.w / 2.0, screen.h / 3.0).round();
+        font.queue(&title, center - glam::vec2(0.0, title.size().y))?;
+        font.queue(&tally, center + glam::vec2(0.0, font.line_height()))
+    }
+}
@@ -0,0 +1,223 @@
+use crate::input::{Input, InputButton};
+use crate::level::collision::CollisionLayer;
+use crate::objects::checkpoint::Checkpoint;
+use crate::objects::general::{Position, Priority};
+use crate::objects::hazards::{Hazard, HazardKind, SpikeDirection, Spikes};
+use crate::objects::object_manager::{ObjectKind, ObjectManager, ObjectPlacement};
+use crate::objects::path_swapper::{PathSwapper, SwapperOrientation};
+use crate::objects::player::{PlayerSpeed, PlayerState};
+use crate::objects::signpost::Signpost;
+use ggez::graphics::{self, Color, DrawMode, MeshBuilder, PxScale, Rect, Text, TextFragment};
+use ggez::{Context, GameResult};
+use glam::*;
+use legion::*;
+
+/// Speed increase of the cursor for each frame it is moving.
+const CURSOR_ACCELERATION: f32 = 0.125;
+
+/// Maximum speed of the cursor, in pixels per frame.
+const CURSOR_MAX_SPEED: f32 = 16.0;
+
+/// An object which can be placed on debug mode or on the level editor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct CatalogueEntry {
+    pub name: &'static str,
+    pub kind: ObjectKind,
+    pub size: Vec2,
+}
+
+/// Builds the catalogue of objects which can be placed on debug mode
+/// or on the level editor.
+pub(crate) fn catalogue() -> Vec<CatalogueEntry> {
+    let spikes = |name, direction, size: Vec2| CatalogueEntry {
+        name,
+        kind: ObjectKind::Spikes(Spikes { direction, size }),
+        size,
+    };
+    let hazard = |name, kind| CatalogueEntry {
+        name,
+        kind: ObjectKind::Hazard(Hazard {
+            kind,
+            size: glam::vec2(64.0, 16.0),
+        }),
+        size: glam::vec2(64.0, 16.0),
+    };
+    vec![
+        spikes("SPIKES UP", SpikeDirection::Up, glam::vec2(32.0, 16.0)),
+        spikes("SPIKES DOWN", SpikeDirection::Down, glam::vec2(32.0, 16.0)),
+        spikes("SPIKES LEFT", SpikeDirection::Left, glam::vec2(16.0, 32.0)),
+        spikes(
+            "SPIKES RIGHT",
+            SpikeDirection::Right,
+            glam::vec2(16.0, 32.0),
+        ),
+        hazard("LAVA", HazardKind::Lava),
+        hazard("ELECTRIC", HazardKind::Electric),
+        CatalogueEntry {
+            name: "CHECKPOINT",
+            kind: ObjectKind::Checkpoint(Checkpoint::default()),
+            size: glam::vec2(16.0, 48.0),
+        },
+        CatalogueEntry {
+            name: "SIGNPOST",
+            kind: ObjectKind::Signpost(Signpost::default()),
+            size: glam::vec2(48.0, 48.0),
+        },
+        CatalogueEntry {
+            name: "PATH SWAPPER",
+            kind: ObjectKind::PathSwapper(PathSwapper {
+                orientation: SwapperOrientation::Vertical,
+                length: 64.0,
+                layer_after: CollisionLayer::B,
+                layer_before: CollisionLayer::A,
+                priority_after: Priority::High,
+                priority_before: Priority::Low,
+                ground_only: true,
+            }),
+            size: glam::vec2(2.0, 64.0),
+        },
+    ]
+}
+
+/// Defines the state of the debug mode, where players are replaced
+/// by a free-flying cursor which can place objects on the level.
+///
+/// A and B cycle through the object catalogue, and C places the
+/// selected object at the cursor position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugMode {
+    /// Position of the cursor on the level.
+    pub cursor: Position,
+    catalogue: Vec<CatalogueEntry>,
+    selected: usize,
+    speed: f32,
+}
+
+impl DebugMode {
+    /// Enters debug mode, with the cursor at a certain position.
+    pub fn new(cursor: Position) -> Self {
+        Self {
+            cursor,
+            catalogue: catalogue(),
+            selected: 0,
+            speed: 0.0,
+        }
+    }
+
+    /// Updates the debug mode, moving the cursor and placing objects.
+    ///
+    /// Players are kept still at the cursor position, so that the
+    /// camera follows it.
+    pub fn update(&mut self, world: &mut World, objects: &mut ObjectManager, input: &Input) {
+        // Move the cursor, accelerating while any direction is held
+        let axis = |negative, positive| {
+            (input.pressing(positive) as i32 - input.pressing(negative) as i32) as f32
+        };
+        let direction = glam::vec2(
+            axis(InputButton::Left, InputButton::Right),
+            axis(InputButton::Up, InputButton::Down),
+        );
+        if direction == Vec2::ZERO {
+            self.speed = 0.0;
+        } else {
+            self.speed = (self.speed + CURSOR_ACCELERATION).min(CURSOR_MAX_SPEED);
+            self.cursor.0 += direction * self.speed.max(1.0);
+        }
+        self.cursor.0 = self.cursor.0.max(Vec2::ZERO);
+
+        // Cycle and place objects
+        let count = self.catalogue.len();
+        if input.pressed(InputButton::A) {
+            self.selected = (self.selected + 1) % count;
+        }
+        if input.pressed(InputButton::B) {
+            self.selected = (self.selected + count - 1) % count;
+        }
+        if input.pressed(InputButton::C) {
+            let kind = self.catalogue[self.selected].kind;
+            objects.add(ObjectPlacement {
+                kind,
+                position: Position::wrap(self.cursor.0.round()),
+            });
+            // Checkpoints are numbered after their order on the level,
+            // so placing one renumbers the ones ahead of it
+            if let ObjectKind::Checkpoint(_) = kind {
+                objects.renumber_checkpoints(world);
+            }
+        }
+
+        let mut query = <(&mut Position, &mut PlayerSpeed, &PlayerState)>::query();
+        for (position, speed, _) in query.iter_mut(world) {
+            *position = self.cursor;
+            *speed = PlayerSpeed::default();
+        }
+    }
+
+    /// Leaves debug mode, giving control back to players at the
+    /// cursor position. Rings, shields and collision layers are kept.
+    pub fn leave(&self, world: &mut World) {
+        let mut query = <(&mut Position, &mut PlayerSpeed, &mut PlayerState)>::query();
+        for (position, speed, state) in query.iter_mut(world) {
+            *position = self.cursor;
+            *speed = PlayerSpeed::default();
+            *state = PlayerState {
+                rings: state.rings,
+                shield: state.shield,
+                layer: state.layer,
+                ..PlayerState::default()
+            };
+        }
+    }
+
+    /// Draws the cursor, the selected object and its name.
+    pub fn draw<F>(&self, context: &mut Context, transform: F) -> GameResult
+    where
+        F: Fn(Vec2) -> Vec2,
+    {
+        let entry = &self.catalogue[self.selected];
+        let center = transform(self.cursor.0);
+        let half = entry.size / 2.0;
+        let color = Color::new(1.0, 1.0, 0.0, 0.8);
+        let mesh = MeshBuilder::new()
+            .rectangle(
+                DrawMode::stroke(1.0),
+                Rect::new(
+                    center.x - half.x,
+                    center.y - half.y,
+                    entry.size.x,
+                    entry.size.y,
+                ),
+                color,
+            )?
+            .line(
+                &[center - glam::vec2(8.0, 0.0), center + glam::vec2(8.0, 0.0)],
+                1.0,
+                color,
+            )?
+            .line(
+                &[center - glam::vec2(0.0, 8.0), center + glam::vec2(0.0, 8.0)],
+                1.0,
+                color,
+            )?
+            .build(context)?;
+        graphics::draw(context, &mesh, (Vec2::ZERO, 0.0, Color::WHITE))?;
+
+        let text = Text::new(
+            TextFragment::new(format!(
+                "DEBUG MODE\n{}\nX {:>5} Y {:>5}",
+                entry.name, self.cursor.0.x as i32, self.cursor.0.y as i32
+            ))
+            .color(Color::new(1.0, 1.0, 0.0, 1.0))
+            .scale(PxScale::from(8.0)),
+        );
+        let screen = graphics::screen_coordinates(context);
+        let dimensions = text.dimensions(context);
+        graphics::queue_text(
+            context,
+            &text,
+            glam::vec2(screen.w - dimensions.w - 5.0, 5.0),
+            None,
+        );
+        Ok(())
+    }
+}
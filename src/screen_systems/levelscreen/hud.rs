@@ -0,0 +1,107 @@
+use crate::font::bitmap_font::BitmapFont;
+use crate::font::layout::{Align, TextStyle};
+use ggez::graphics::Color;
+use ggez::{Context, GameResult};
+
+/// Path to the font of the HUD.
+const HUD_FONT_PATH: &str = "/fonts/default.json";
+
+/// Position of the top left corner of the HUD, in virtual pixels.
+const HUD_LEFT: f32 = 16.0;
+const HUD_TOP: f32 = 8.0;
+
+/// Vertical distance between HUD lines, in pixels.
+const LINE_HEIGHT: f32 = 12.0;
+
+/// Distance from the left of the HUD to the right edge of values, in
+/// pixels.
+const VALUE_RIGHT: f32 = 84.0;
+
+/// Elapsed time where the timer starts warning about the time limit,
+/// in frames.
+const TIME_WARNING: u64 = 9 * 60 * 60;
+
+/// Amount of frames warnings stay on and off while flashing.
+const FLASH_FRAMES: u32 = 8;
+
+const LABEL_COLOR: Color = Color::new(1.0, 1.0, 0.0, 1.0);
+const VALUE_COLOR: Color = Color::WHITE;
+const WARNING_COLOR: Color = Color::new(1.0, 0.0, 0.0, 1.0);
+
+/// Shows the score, the elapsed time and the rings of the player
+/// using a bitmap font.
+///
+/// The HUD is drawn in virtual pixels, so it is not affected by the
+/// camera. The rings counter flashes red while the player has no
+/// rings, and the timer flashes red close to the time limit.
+pub struct Hud {
+    font: BitmapFont,
+    /// Whether the timer shows centiseconds.
+    pub centiseconds: bool,
+    timer: u32,
+}
+
+/// Formats an elapsed time in frames as minutes and seconds, and
+/// optionally centiseconds.
+fn format_time(time: u64, centiseconds: bool) -> String {
+    let minutes = time / 3600;
+    let seconds = (time / 60) % 60;
+    if centiseconds {
+        format!("{}'{:02}\"{:02}", minutes, seconds, (time % 60) * 100 / 60)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+impl Hud {
+    /// Creates a new HUD, loading its font.
+    pub fn new(context: &mut Context) -> GameResult<Self> {
+        Ok(Self {
+            font: BitmapFont::load(context, HUD_FONT_PATH)?,
+            centiseconds: false,
+            timer: 0,
+        })
+    }
+
+    /// Queues a labelled value on a line of the HUD, with the value
+    /// aligned to the right.
+    fn queue_line(&mut self, line: usize, label: &str, value: &str, warning: bool) -> GameResult {
+        let (label_color, value_color) = if warning {
+            (WARNING_COLOR, WARNING_COLOR)
+        } else {
+            (LABEL_COLOR, VALUE_COLOR)
+        };
+        let position = glam::vec2(HUD_LEFT, HUD_TOP + line as f32 * LINE_HEIGHT);
+        let label_style = TextStyle {
+            color: label_color,
+            ..TextStyle::default()
+        };
+        let value_style = TextStyle {
+            align: Align::Right,
+            color: value_color,
+            ..TextStyle::default()
+        };
+        self.font.queue_text(label, position, &label_style)?;
+        self.font
+            .queue_text(value, position + glam::vec2(VALUE_RIGHT, 0.0), &value_style)
+    }
+
+    /// Advances the HUD by a single frame and queues it for drawing,
+    /// given the score, the elapsed time in frames and the rings of
+    /// the player.
+    pub fn update(&mut self, score: u32, time: u64, rings: u32) -> GameResult {
+        self.timer = self.timer.wrapping_add(1);
+        let flash = (self.timer / FLASH_FRAMES) % 2 == 1;
+        let time_text = format_time(time, self.centiseconds);
+
+        self.font.clear();
+        self.queue_line(0, "SCORE", &score.to_string(), false)?;
+        self.queue_line(1, "TIME", &time_text, flash && (time >= TIME_WARNING))?;
+        self.queue_line(2, "RINGS", &rings.to_string(), flash && (rings == 0))
+    }
+
+    /// Draws the queued HUD.
+    pub fn draw(&self, context: &mut Context) -> GameResult {
+        self.font.draw(context)
+    }
+}
@@ -1 +1,4 @@
+pub mod act_clear;
+pub mod debug_mode;
+pub mod hud;
 pub mod system;
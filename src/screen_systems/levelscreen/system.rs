@@ -1,20 +1,76 @@
+use super::act_clear::{self, ActClear};
+use super::debug_mode::DebugMode;
+use super::hud::Hud;
+use crate::font::bitmap_font::BitmapFont;
 use crate::input::{Input, InputButton};
+use crate::level::background::{Background, BackgroundData};
+use crate::level::collision::CollisionMap;
+use crate::level::format::LevelData;
+use crate::level::manifest::{ActData, ActId, ManifestData, MANIFEST_PATH};
+use crate::level::tilemap::{TileAnimations, TilemapRenderer};
+use crate::objects::afterimage::Afterimage;
 use crate::objects::animation::*;
-use crate::objects::camera::Camera;
+use crate::objects::arena::ArenaLock;
+use crate::objects::camera::{Camera, CameraBounds, CameraRegion};
+use crate::objects::checkpoint::{Checkpoint, CheckpointRecord};
 use crate::objects::general::*;
+use crate::objects::hazards;
+use crate::objects::object_manager::ObjectManager;
+use crate::objects::palette::{Palette, PaletteData};
+use crate::objects::particles::{ParticleEmitter, ParticleKind, ParticleSystem};
+use crate::objects::path_swapper::PathSwapper;
 use crate::objects::player::{self, *};
+use crate::objects::signpost::Signpost;
 use crate::objects::sprite_atlas::SpriteAtlas;
-use crate::screen_systems::Navigation;
+use crate::objects::water::Water;
+use crate::screen_systems::{Navigation, TransitionEffect, Transitions};
+use ggez::audio::{self, SoundSource};
+use ggez::graphics::{self, Color, Image};
 use ggez::{Context, GameResult};
+use glam::Vec2;
 use legion::*;
+use std::collections::HashMap;
+
+/// State of a player when an act is cleared, which may be carried
+/// into the next act.
+type CarriedPlayer = (PlayerState, Position, PlayerSpeed);
 
 /// Defines the state for a level screen system.
 pub struct LevelScreenSystem {
     world: World,
     first_update: bool,
-    debug: bool,
+    debug: Option<DebugMode>,
     camera: Option<Camera>,
     camera_timer: i32,
+    death_timer: i32,
+    timer: u64,
+    checkpoint: Option<CheckpointRecord>,
+    act_clear: Option<ActClear>,
+    hud: Option<Hud>,
+    tally_font: Option<BitmapFont>,
+    score: u32,
+    /// Amount of rings placed on the level, for the perfect bonus.
+    /// Levels have no ring objects yet, so no perfect bonus is given.
+    total_rings: u32,
+    objects: ObjectManager,
+    terrain: Option<CollisionMap>,
+    start: Position,
+    bounds: CameraBounds,
+    regions: Vec<CameraRegion>,
+    level: Option<LevelData>,
+    foreground: Option<TilemapRenderer>,
+    background: Option<TilemapRenderer>,
+    animations: Option<TileAnimations>,
+    palette: Option<Palette>,
+    particles: Option<ParticleSystem>,
+    parallax: Option<Background>,
+    manifest: Option<ManifestData>,
+    act: ActId,
+    constants: PlayerConstants,
+    water: Option<Water>,
+    music: Option<(String, audio::Source)>,
+    play_testing: bool,
+    reload: bool,
 }
 
 impl LevelScreenSystem {
@@ -22,43 +78,445 @@ impl LevelScreenSystem {
     pub fn new() -> Self {
         let world = World::default();
         let first_update = true;
-        let debug = false;
+        let debug = None;
         Self {
             world,
             first_update,
             debug,
             camera: None,
             camera_timer: 0,
+            death_timer: 0,
+            timer: 0,
+            checkpoint: None,
+            act_clear: None,
+            hud: None,
+            tally_font: None,
+            score: 0,
+            total_rings: 0,
+            objects: ObjectManager::new(Vec::new()),
+            terrain: None,
+            start: Position::default(),
+            bounds: CameraBounds::default(),
+            regions: Vec::new(),
+            level: None,
+            foreground: None,
+            background: None,
+            animations: None,
+            palette: None,
+            particles: None,
+            parallax: None,
+            manifest: None,
+            act: ActId::default(),
+            constants: PlayerConstants::default(),
+            water: None,
+            music: None,
+            play_testing: false,
+            reload: false,
         }
     }
 
     /// Sets up the initial state of the level screen system.
     pub fn setup(&mut self, context: &mut Context) -> GameResult {
         self.camera = Some(Camera::new(context));
-        Player::create(context, &mut self.world, false)?;
+        self.particles = Some(ParticleSystem::new(context)?);
+        let manifest = ManifestData::load(context, MANIFEST_PATH)?;
+        let mut hud = Hud::new(context)?;
+        hud.centiseconds = manifest.centiseconds;
+        self.hud = Some(hud);
+        self.tally_font = Some(BitmapFont::load(context, act_clear::FONT_PATH)?);
+        Player::create(context, &mut self.world, false, manifest.rotation)?;
+        self.manifest = Some(manifest);
+        self.load_act(context, self.act)
+    }
+
+    /// Gets the data of the current act.
+    fn act_data(&self) -> Option<&ActData> {
+        self.manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get(self.act))
+            .map(|(_, act)| act)
+    }
+
+    /// Loads an act of the manifest, along with its level, background,
+    /// music, physics and water.
+    fn load_act(&mut self, context: &mut Context, id: ActId) -> GameResult {
+        self.act = id;
+        let act = match self.act_data() {
+            Some(act) => act.clone(),
+            None => return Ok(()),
+        };
+
+        let level = LevelData::load(context, &act.level)?;
+        self.load(context, level)?;
+
+        let background = BackgroundData::load(context, &act.background)?;
+        self.parallax = Some(Background::new(context, &background)?);
+
+        self.constants = act.physics.constants();
+        self.water = act.water.map(|water| Water {
+            height: water.height,
+            color: Color::from(water.color),
+        });
+
+        // Keep the music playing if the next act uses the same track
+        let playing = self.music.as_ref().map(|(path, _)| path.clone());
+        if act.music != playing {
+            if let Some((_, mut music)) = self.music.take() {
+                music.stop(context)?;
+            }
+            if let Some(path) = act.music {
+                let mut music = audio::Source::new(context, &path)?;
+                music.set_repeat(true);
+                music.play(context)?;
+                self.music = Some((path, music));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the current level with the given level data.
+    fn load(&mut self, context: &mut Context, level: LevelData) -> GameResult {
+        self.objects.reset(&mut self.world);
+        self.terrain = Some(level.collision_map());
+        self.objects = ObjectManager::new(level.object_layout());
+        self.start = level.start_position();
+        self.bounds = level.camera_bounds();
+        self.regions = level.camera_regions();
+        let tileset = Image::new(context, &level.tileset)?;
+        self.palette = match &level.palette {
+            Some(path) => {
+                let palette = PaletteData::load(context, path)?;
+                Some(Palette::new(context, &tileset, &palette)?)
+            }
+            None => None,
+        };
+        self.foreground = Some(TilemapRenderer::new(tileset.clone()));
+        self.background = Some(TilemapRenderer::new(tileset));
+        self.animations = Some(TileAnimations::new(&level));
+        if let Some(particles) = self.particles.as_mut() {
+            particles.clear();
+        }
+        self.level = Some(level);
+        Ok(())
+    }
+
+    /// Starts play testing a level from the level editor, with
+    /// players starting at a certain position.
+    ///
+    /// Leaving the level goes back to the level editor, and the
+    /// regular level is loaded again the next time the level
+    /// screen is entered.
+    pub fn play_test(
+        &mut self,
+        context: &mut Context,
+        level: LevelData,
+        start: Position,
+    ) -> GameResult {
+        self.load(context, level)?;
+        self.start = start;
+        self.play_testing = true;
+        self.reload = false;
+        self.first_update = true;
+        self.debug = None;
+        Ok(())
+    }
+
+    /// Leaves the level screen, going back to the screen it was
+    /// entered from. The first act is loaded again the next time
+    /// the level screen is entered.
+    fn leave(&mut self, context: &mut Context, transitions: &mut Transitions) -> GameResult {
+        self.first_update = true;
+        self.reload = true;
+        self.act = ActId::default();
+        if let Some((_, mut music)) = self.music.take() {
+            music.stop(context)?;
+        }
+        if self.play_testing {
+            self.play_testing = false;
+            transitions.request(
+                Navigation::LevelEditor,
+                TransitionEffect::Fade(Color::BLACK),
+                12,
+            );
+        } else {
+            transitions.request(
+                Navigation::TitleScreen,
+                TransitionEffect::PaletteFadeBlack,
+                24,
+            );
+        }
+        Ok(())
+    }
+
+    /// Starts the current act from scratch, keeping the score.
+    fn start_act(&mut self) {
+        self.checkpoint = None;
+        self.act_clear = None;
+        self.objects.reset(&mut self.world);
+        self.respawn();
+    }
+
+    /// Advances to the next act after clearing the current one, or
+    /// leaves the level screen if there are no more acts.
+    ///
+    /// Depending on the current act, players carry their rings and
+    /// shields, and possibly their position, into the next act.
+    fn advance(&mut self, context: &mut Context, transitions: &mut Transitions) -> GameResult {
+        let next = match &self.manifest {
+            Some(manifest) if !self.play_testing => manifest.next(self.act),
+            _ => None,
+        };
+        let next = match next {
+            Some(next) => next,
+            None => return self.leave(context, transitions),
+        };
+
+        let carry_over = self.act_data().and_then(|act| act.carry_over);
+        let carried: HashMap<Entity, CarriedPlayer> =
+            <(Entity, &PlayerState, &Position, &PlayerSpeed)>::query()
+                .iter(&self.world)
+                .map(|(entity, state, position, speed)| (*entity, (*state, *position, *speed)))
+                .collect();
+
+        self.load_act(context, next)?;
+        self.start_act();
+
+        let carry_over = match carry_over {
+            Some(carry_over) => carry_over,
+            None => return Ok(()),
+        };
+        let offset = glam::vec2(carry_over.offset[0], carry_over.offset[1]);
+        let mut query = <(Entity, &mut PlayerState, &mut Position, &mut PlayerSpeed)>::query();
+        for (entity, state, position, speed) in query.iter_mut(&mut self.world) {
+            let (old_state, old_position, old_speed) = match carried.get(entity) {
+                Some(carried) => *carried,
+                None => continue,
+            };
+            if carry_over.position {
+                *state = PlayerState {
+                    autorun: false,
+                    underwater: false,
+                    ..old_state
+                };
+                *position = Position::wrap(old_position.0 + offset);
+                *speed = old_speed;
+            } else {
+                state.rings = old_state.rings;
+                state.shield = old_state.shield;
+            }
+        }
+        if carry_over.position {
+            // Trails were recorded on the coordinates of the last act
+            for afterimage in <&mut Afterimage>::query().iter_mut(&mut self.world) {
+                afterimage.clear();
+            }
+            let followed = <(&PlayerState, &Position)>::query()
+                .iter(&self.world)
+                .next()
+                .map(|(_, position)| *position);
+            if let (Some(camera), Some(position)) = (self.camera.as_mut(), followed) {
+                camera.reset(&position);
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the camera bounds of the first camera region containing
+    /// a point, or the level camera bounds if there is none.
+    fn region_bounds(&self, point: Vec2) -> CameraBounds {
+        self.regions
+            .iter()
+            .find(|region| region.contains(point))
+            .map(|region| region.bounds)
+            .unwrap_or(self.bounds)
+    }
+
+    /// Respawns players on the last activated checkpoint, or on the
+    /// start of the level if no checkpoint was activated.
+    ///
+    /// Camera bounds are set immediately, releasing any arena lock.
+    fn respawn(&mut self) {
+        let (position, time, bounds) = match &self.checkpoint {
+            Some(record) => (record.position, record.time, record.camera_bounds),
+            None => (self.start, 0, self.region_bounds(self.start.0)),
+        };
+
+        Player::respawn_all(&mut self.world, &position);
+        for constants in <&mut PlayerConstants>::query().iter_mut(&mut self.world) {
+            *constants = self.constants;
+        }
+        self.objects.respawn(&mut self.world);
+        self.timer = time;
+        if let Some(camera) = self.camera.as_mut() {
+            camera.snap_bounds(bounds);
+            camera.reset(&position);
+        }
+    }
+
+    /// Updates players and the objects they interact with.
+    fn update_players(
+        &mut self,
+        context: &mut Context,
+        transitions: &mut Transitions,
+        input: &Input,
+    ) -> GameResult {
+        // Splash wherever players cross the water surface
+        if let Some(water) = &self.water {
+            let splashes = water.update(&mut self.world, &self.constants);
+            if let Some(particles) = self.particles.as_mut() {
+                for point in splashes {
+                    let splash = ParticleEmitter::burst(
+                        ParticleKind::Splash,
+                        6,
+                        glam::vec2(0.0, -3.0),
+                        glam::vec2(1.5, 1.0),
+                    );
+                    particles.add_emitter(splash, point);
+                }
+            }
+        }
+        player::animation::update(&mut self.world)?;
+        player::physics::update(&mut self.world, input, self.terrain.as_ref())?;
+        if let Some(camera) = &self.camera {
+            player::physics::update_bounds(&mut self.world, &camera.bounds)?;
+        }
+        PathSwapper::update(&mut self.world)?;
+        hazards::update(&mut self.world)?;
+
+        // Lock the camera into arenas entered by players
+        if let Some(bounds) = ArenaLock::update(&mut self.world)? {
+            if let Some(camera) = self.camera.as_mut() {
+                camera.lock(bounds);
+            }
+        }
+
+        // Record activated checkpoints
+        if let Some((index, position)) =
+            Checkpoint::update(&mut self.world, self.checkpoint.map(|record| record.index))?
+        {
+            self.checkpoint = Some(CheckpointRecord {
+                index,
+                position,
+                time: self.timer,
+                camera_bounds: self
+                    .camera
+                    .as_ref()
+                    .map(|camera| camera.target_bounds())
+                    .unwrap_or_default(),
+            });
+        }
+
+        // Start act clear sequence when passing by a signpost, locking
+        // the camera horizontally
+        let passed = Signpost::update(&mut self.world)?.is_some();
+        if passed && self.act_clear.is_none() {
+            let seamless = self
+                .act_data()
+                .and_then(|act| act.carry_over)
+                .map(|carry_over| carry_over.position)
+                .unwrap_or(false);
+            self.act_clear = Some(ActClear::new(self.timer, seamless));
+            if let Some(camera) = self.camera.as_mut() {
+                let view = camera.view_rect();
+                camera.lock(CameraBounds {
+                    min: glam::vec2(view.left(), camera.bounds.min.y),
+                    max: glam::vec2(view.right(), camera.bounds.max.y),
+                });
+            }
+        }
+
+        let cleared = match self.act_clear.as_mut() {
+            Some(act_clear) => {
+                act_clear.update(&mut self.world, &mut self.score, self.total_rings, input)
+            }
+            None => false,
+        };
+        if cleared {
+            self.advance(context, transitions)?;
+        }
+
+        // Respawn some time after dying. The level timer stops
+        // while dead or after clearing the act
+        let dead = <&PlayerState>::query()
+            .iter(&self.world)
+            .any(|state| state.is_dead());
+        self.death_timer = if dead { self.death_timer + 1 } else { 0 };
+        if !dead && self.act_clear.is_none() {
+            self.timer += 1;
+        }
+        if self.death_timer >= 120 {
+            self.death_timer = 0;
+            self.respawn();
+        }
         Ok(())
     }
 
     /// Updates the level screen system.
-    pub fn update(&mut self, navigation: &mut Navigation, input: &Input) -> GameResult {
+    pub fn update(
+        &mut self,
+        context: &mut Context,
+        transitions: &mut Transitions,
+        input: &Input,
+    ) -> GameResult {
         if self.first_update {
             self.first_update = false;
-            Player::respawn_all(&mut self.world);
+            if self.reload {
+                self.reload = false;
+                self.load_act(context, self.act)?;
+            }
+            self.score = 0;
+            self.start_act();
+        }
+        // Update players, unless they are replaced by the debug mode
+        // cursor
+        if let Some(debug) = self.debug.as_mut() {
+            debug.update(&mut self.world, &mut self.objects, input);
+            // Follow the activated checkpoint if placed checkpoints
+            // renumbered it
+            if let Some(record) = self.checkpoint.as_mut() {
+                if let Some(index) = self.objects.checkpoint_index(record.position) {
+                    record.index = index;
+                }
+            }
+        } else {
+            self.update_players(context, transitions, input)?;
         }
-        // Update players
-        player::animation::update(&mut self.world)?;
-        player::physics::update(&mut self.world, input)?;
 
         // Update all animated sprites
-        let mut query = <(&mut Animator, &mut SpriteAtlas, &Position)>::query();
-        for (animator, atlas, position) in query.iter_mut(&mut self.world) {
+        Palette::update_sprites(&mut self.world);
+        let debug = self.debug.is_some();
+        let mut query = <(
+            &mut Animator,
+            &mut SpriteAtlas,
+            &Position,
+            Option<&PlayerState>,
+            Option<&mut Afterimage>,
+        )>::query();
+        for (animator, atlas, position, state, afterimage) in query.iter_mut(&mut self.world) {
             let hotspot = Position::wrap(if let Some(camera) = &self.camera {
                 camera.transform(position.0)
             } else {
                 position.0
             });
             atlas.clear();
-            animator.update(atlas, &hotspot)?;
+
+            // Blink while invulnerable, and hide players on debug mode
+            let hidden = state
+                .map(|state| debug || (state.invulnerable / 4) % 2 == 1)
+                .unwrap_or(false);
+            if !hidden {
+                animator.update(atlas, &hotspot)?;
+            }
+            if let Some(afterimage) = afterimage {
+                let camera = &self.camera;
+                afterimage.update(animator, atlas, position.0, !hidden, |point| {
+                    if let Some(camera) = camera {
+                        camera.transform(point)
+                    } else {
+                        point
+                    }
+                })?;
+            }
         }
 
         // Update camera panning
@@ -81,6 +539,7 @@ impl LevelScreenSystem {
                 use crate::objects::camera::{
                     CameraDisplacementBehaviour, CameraVerticalBehaviour,
                 };
+                let bounds = self.region_bounds(position.0);
                 let camera = self.camera.as_mut().unwrap();
 
                 let vbehaviour = if !state.get_ground() {
@@ -110,73 +569,86 @@ impl LevelScreenSystem {
 
                 camera.vertical_behaviour = vbehaviour;
                 camera.displacement_behaviour = dbehaviour;
+                camera.set_bounds(bounds);
                 camera.update(Some(position))?;
             }
         }
 
+        // Spawn and despawn objects around the camera, and queue
+        // the visible tiles
+        if let Some(camera) = &self.camera {
+            self.objects.update(&mut self.world, camera.view_rect());
+            if let Some(parallax) = self.parallax.as_mut() {
+                parallax.update();
+                parallax.queue(camera.view_rect());
+            }
+            if let Some(animations) = self.animations.as_mut() {
+                animations.update();
+            }
+            if let Some(particles) = self.particles.as_mut() {
+                particles.update(&mut self.world);
+                particles.queue(|position| camera.transform(position))?;
+            }
+            if let Some(image) = self.palette.as_mut().and_then(Palette::update) {
+                if let Some(foreground) = self.foreground.as_mut() {
+                    foreground.set_texture(image.clone());
+                }
+                if let Some(background) = self.background.as_mut() {
+                    background.set_texture(image.clone());
+                }
+            }
+            if let (Some(level), Some(animations)) = (&self.level, &self.animations) {
+                let transform = |vertex| camera.transform(vertex);
+                let view = camera.view_rect();
+                if let Some(background) = self.background.as_mut() {
+                    background.queue(level, &level.background, animations, view, transform);
+                }
+                if let Some(foreground) = self.foreground.as_mut() {
+                    foreground.queue(level, &level.foreground, animations, view, transform);
+                }
+            }
+        }
+
+        // Show the rings of the first player on the HUD
+        if let Some(hud) = self.hud.as_mut() {
+            let rings = <&PlayerState>::query()
+                .iter(&self.world)
+                .next()
+                .map(|state| state.rings)
+                .unwrap_or(0);
+            hud.update(self.score, self.timer, rings)?;
+        }
+        if let (Some(act_clear), Some(font)) = (&self.act_clear, self.tally_font.as_mut()) {
+            act_clear.queue(font, self.score, graphics::screen_coordinates(context))?;
+        }
+
         if input.pressed(InputButton::Back) {
-            self.first_update = true;
-            *navigation = Navigation::TitleScreen;
+            self.leave(context, transitions)?;
         }
 
+        // Enter debug mode at the player position, or leave it
+        // giving control back at the cursor position. The cursor
+        // is free to leave arenas
         if input.pressed(InputButton::Debug) {
-            self.debug = !self.debug;
+            if let Some(camera) = self.camera.as_mut() {
+                camera.release();
+            }
+            self.debug = match self.debug.take() {
+                Some(debug) => {
+                    debug.leave(&mut self.world);
+                    self.death_timer = 0;
+                    None
+                }
+                None => <(&Position, &PlayerState)>::query()
+                    .iter(&self.world)
+                    .next()
+                    .map(|(position, _)| DebugMode::new(*position)),
+            };
         }
 
         Ok(())
     }
 
-    fn draw_test_graphics(&self, context: &mut Context) -> GameResult {
-        // Draw a grid for camera testing
-        use crate::objects::player::physics::FAKE_GROUND_Y;
-        use ggez::graphics::{self, Color, MeshBuilder};
-        use glam::*;
-        let mut builder = MeshBuilder::new();
-
-        // Crosses on background
-        for i in 0..30 {
-            for j in 0..30 {
-                let center = glam::vec2(100.0 * i as f32, 100.0 * j as f32);
-                let _ = builder
-                    .line(
-                        &[
-                            glam::vec2(center.x, center.y - 10.0),
-                            glam::vec2(center.x, center.y + 10.0),
-                        ],
-                        1.0,
-                        Color::new(0.0, 1.0, 1.0, 0.3),
-                    )?
-                    .line(
-                        &[
-                            glam::vec2(center.x - 10.0, center.y),
-                            glam::vec2(center.x + 10.0, center.y),
-                        ],
-                        1.0,
-                        Color::new(0.0, 1.0, 1.0, 0.3),
-                    )?;
-            }
-        }
-
-        // Representation for floor
-        let _ = builder.line(
-            &[
-                glam::vec2(0.0, FAKE_GROUND_Y + 16.0 + 5.0),
-                glam::vec2(3000.0, FAKE_GROUND_Y + 16.0 + 5.0),
-            ],
-            5.0,
-            Color::new(0.3, 0.0, 0.2, 0.5),
-        )?;
-
-        let mesh = builder.build(context)?;
-        let position = if let Some(camera) = &self.camera {
-            camera.transform(Vec2::ZERO)
-        } else {
-            Vec2::ZERO
-        };
-        graphics::draw(context, &mesh, (position, 0.0, Color::WHITE))?;
-        Ok(())
-    }
-
     fn draw_debug_text(
         &self,
         context: &mut Context,
@@ -208,25 +680,89 @@ impl LevelScreenSystem {
 
         let text = TextFragment::new(hud_text)
             .color(Color::WHITE)
-            .scale(PxScale::from(12.0));
+            .scale(PxScale::from(8.0));
         let text = Text::new(text);
-        graphics::queue_text(context, &text, glam::vec2(10.0, 10.0), None);
+        graphics::queue_text(context, &text, glam::vec2(5.0, 5.0), None);
+        Ok(())
+    }
+
+    /// Draws all animated sprites of a certain priority, ordered by
+    /// their draw layers. Afterimages are drawn right behind their
+    /// sprites.
+    fn draw_sprites(&self, context: &mut Context, priority: Priority) -> GameResult {
+        let mut query = <(
+            &SpriteAtlas,
+            Option<&Priority>,
+            &DrawLayer,
+            Option<&Afterimage>,
+        )>::query();
+        let mut sprites: Vec<(DrawLayer, &SpriteAtlas, Option<&Afterimage>)> = query
+            .iter(&self.world)
+            .filter(|(_, sprite_priority, _, _)| {
+                sprite_priority.copied().unwrap_or_default() == priority
+            })
+            .map(|(atlas, _, layer, afterimage)| (*layer, atlas, afterimage))
+            .collect();
+        // Query order changes as entities are spawned and despawned, so
+        // draw layers carry their own order to break ties
+        sprites.sort_by_key(|(layer, _, _)| *layer);
+        for (_, atlas, afterimage) in sprites {
+            if let Some(afterimage) = afterimage {
+                afterimage.draw(context)?;
+            }
+            atlas.draw(context)?;
+        }
         Ok(())
     }
 
     /// Draws the level screen.
     pub fn draw(&self, context: &mut Context) -> GameResult {
-        // Draw test graphics
-        self.draw_test_graphics(context)?;
+        // Draw background layers
+        if let Some(parallax) = &self.parallax {
+            parallax.draw(context)?;
+        }
 
-        // Draw all animated sprites
-        let mut query = <&SpriteAtlas>::query();
-        for atlas in query.iter(&self.world) {
-            atlas.draw(context)?;
+        // Draw low priority planes
+        if let Some(background) = &self.background {
+            background.draw(context, Priority::Low)?;
+        }
+        if let Some(foreground) = &self.foreground {
+            foreground.draw(context, Priority::Low)?;
+        }
+
+        // Draw level objects
+        let transform = |vertex| {
+            if let Some(camera) = &self.camera {
+                camera.transform(vertex)
+            } else {
+                vertex
+            }
+        };
+        hazards::draw(&self.world, context, transform)?;
+        Checkpoint::draw(&self.world, context, transform)?;
+        Signpost::draw(&self.world, context, transform)?;
+
+        // Draw sprites and particles between the low and high
+        // priority planes
+        self.draw_sprites(context, Priority::Low)?;
+        if let Some(particles) = &self.particles {
+            particles.draw(context)?;
+        }
+        if let Some(background) = &self.background {
+            background.draw(context, Priority::High)?;
+        }
+        if let Some(foreground) = &self.foreground {
+            foreground.draw(context, Priority::High)?;
+        }
+        self.draw_sprites(context, Priority::High)?;
+
+        // Draw water over everything below its surface
+        if let Some(water) = &self.water {
+            water.draw(context, transform)?;
         }
 
         // Draw sensors and camera
-        if self.debug {
+        if let Some(debug) = &self.debug {
             let mut query = <(&PlayerState, &Position, &PlayerSpeed)>::query();
             for (state, position, speed) in query.iter(&self.world) {
                 let hotspot = Position::wrap(if let Some(camera) = &self.camera {
@@ -239,8 +775,30 @@ impl LevelScreenSystem {
             }
 
             if let Some(camera) = &self.camera {
+                if let Some(terrain) = &self.terrain {
+                    let layer = <&PlayerState>::query()
+                        .iter(&self.world)
+                        .next()
+                        .map(|state| state.layer)
+                        .unwrap_or_default();
+                    terrain.debug_draw(context, layer, camera.view_rect(), transform)?;
+                }
                 camera.debug_draw(context)?;
             }
+            PathSwapper::debug_draw(&self.world, context, transform)?;
+            ArenaLock::debug_draw(&self.world, context, transform)?;
+            debug.draw(context, transform)?;
+        }
+
+        // Draw the HUD, which is replaced by the debug text on debug
+        // mode
+        if let (Some(hud), None) = (&self.hud, &self.debug) {
+            hud.draw(context)?;
+        }
+
+        // Draw act clear overlay
+        if let (Some(_), Some(font)) = (&self.act_clear, &self.tally_font) {
+            font.draw(context)?;
         }
 
         Ok(())
@@ -1,23 +1,32 @@
+mod leveleditor;
 mod levelscreen;
 mod navigation;
 mod titlescreen;
+mod transition;
 
 use crate::Input;
 use ggez::graphics::{self, DrawParam, FilterMode};
 use ggez::Context;
 use ggez::GameResult;
+pub use leveleditor::system::LevelEditorSystem;
 pub use levelscreen::system::LevelScreenSystem;
 pub use navigation::Navigation;
 pub use titlescreen::system::TitleScreenSystem;
+pub use transition::{TransitionEffect, Transitions, WipeDirection};
 
 /// Represents a collection of screen systems, which can
 /// be switched between.
 ///
 /// The screen system is responsible for updating and drawing
-/// the current screen.
+/// the current screen. Screens request changes through transitions,
+/// and the current screen is switched once they cover it.
 pub struct ScreenSystems {
     title_screen: TitleScreenSystem,
     level_screen: LevelScreenSystem,
+    level_editor: LevelEditorSystem,
+    transitions: Transitions,
+    /// Input seen by screens while transitions block input.
+    blocked_input: Input,
 }
 
 impl ScreenSystems {
@@ -25,24 +34,46 @@ impl ScreenSystems {
     pub fn new(game_title: &str) -> Self {
         let title_screen = TitleScreenSystem::new(game_title);
         let level_screen = LevelScreenSystem::new();
+        let level_editor = LevelEditorSystem::new();
         Self {
             title_screen,
             level_screen,
+            level_editor,
+            transitions: Transitions::default(),
+            blocked_input: Input::default(),
         }
     }
 
     /// Updates the current screen.
     pub fn update(
         &mut self,
-        _context: &mut Context,
+        context: &mut Context,
         navigation: &mut Navigation,
         input: &Input,
     ) -> GameResult {
+        if self.transitions.freezes() {
+            self.transitions.update(navigation);
+            return Ok(());
+        }
+        let input = if self.transitions.blocks_input() {
+            &self.blocked_input
+        } else {
+            input
+        };
+        let transitions = &mut self.transitions;
         match navigation {
-            Navigation::TitleScreen => self.title_screen.update(navigation, input)?,
-            Navigation::LevelScreen => self.level_screen.update(navigation, input)?,
+            Navigation::TitleScreen => self.title_screen.update(transitions, input)?,
+            Navigation::LevelScreen => self.level_screen.update(context, transitions, input)?,
+            Navigation::LevelEditor => {
+                self.level_editor.update(context, transitions, input)?;
+                // Levels being play tested are handed to the level screen
+                if let Some((level, start)) = self.level_editor.take_play_test() {
+                    self.level_screen.play_test(context, level, start)?;
+                }
+            }
             Navigation::Settings => {}
         };
+        self.transitions.update(navigation);
         Ok(())
     }
 
@@ -50,6 +81,7 @@ impl ScreenSystems {
     pub fn setup(&mut self, context: &mut Context) -> GameResult {
         self.title_screen.setup(context)?;
         self.level_screen.setup(context)?;
+        self.level_editor.setup(context)?;
         Ok(())
     }
 
@@ -58,10 +90,11 @@ impl ScreenSystems {
         match navigation {
             Navigation::TitleScreen => self.title_screen.draw(context)?,
             Navigation::LevelScreen => self.level_screen.draw(context)?,
+            Navigation::LevelEditor => self.level_editor.draw(context)?,
             Navigation::Settings => {}
         };
 
         graphics::draw_queued_text(context, DrawParam::new(), None, FilterMode::Linear)?;
-        Ok(())
+        self.transitions.draw(context)
     }
 }
@@ -2,9 +2,11 @@
 
 /// Enumeration to determine the current
 /// screen being drawn and updated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Navigation {
     TitleScreen,
     LevelScreen,
+    LevelEditor,
     Settings,
 }
 
@@ -1,5 +1,5 @@
 use crate::objects::general::{Position, Tag};
-use crate::screen_systems::Navigation;
+use crate::screen_systems::{Navigation, TransitionEffect, Transitions, WipeDirection};
 use crate::{Input, InputButton};
 use ggez::graphics::MeshBuilder;
 use ggez::graphics::{self, Color};
@@ -18,7 +18,7 @@ pub struct Marker {
 
 impl Marker {
     /// Updates all markers in the world.
-    pub fn update(world: &mut World, navigation: &mut Navigation, input: &Input) -> GameResult {
+    pub fn update(world: &mut World, transitions: &mut Transitions, input: &Input) -> GameResult {
         let mut query = <(&Marker, &mut Tag, &mut Position)>::query();
         for (marker, tag, position) in query.iter_mut(world) {
             if input.pressed(InputButton::Down) {
@@ -34,8 +34,20 @@ impl Marker {
             tag.0 %= marker.num_options;
             position.0.y = tag.0 as f32 * marker.draw_step;
 
-            if (tag.0 == 0) && input.pressed(InputButton::Start) {
-                *navigation = Navigation::LevelScreen;
+            if input.pressed(InputButton::Start) {
+                match tag.0 {
+                    0 => transitions.request(
+                        Navigation::LevelScreen,
+                        TransitionEffect::PaletteFadeBlack,
+                        24,
+                    ),
+                    2 => transitions.request(
+                        Navigation::LevelEditor,
+                        TransitionEffect::Wipe(WipeDirection::Left),
+                        20,
+                    ),
+                    _ => {}
+                }
             }
         }
 
@@ -50,8 +62,8 @@ impl Marker {
                 .triangles(
                     &[
                         glam::vec2(0.0, 0.0),
-                        glam::vec2(0.0, 10.0),
-                        glam::vec2(10.0, 5.0),
+                        glam::vec2(0.0, 5.0),
+                        glam::vec2(5.0, 2.5),
                     ],
                     Color::WHITE,
                 )?
@@ -1,115 +1,96 @@
 use super::objects;
+use crate::font::bitmap_font::BitmapFont;
+use crate::font::layout::{Align, TextStyle};
 use crate::objects::general::{Position, Tag};
-use crate::screen_systems::Navigation;
+use crate::screen_systems::Transitions;
 use crate::Input;
-use ggez::graphics::{self, Text};
-use ggez::graphics::{Color, PxScale, TextFragment};
+use ggez::graphics;
 use ggez::Context;
 use ggez::GameResult;
 use legion::*;
 
+/// Path to the font of the title screen.
+const FONT_PATH: &str = "/fonts/default.json";
+
+/// Options of the title screen menu, in order.
+const MENU_OPTIONS: [&str; 3] = ["Play", "Settings", "Level Editor"];
+
+/// Scale factor of the game title.
+const TITLE_SCALE: f32 = 3.0;
+
 /// Defines the state of the title screen system.
 pub struct TitleScreenSystem {
-    title: TextFragment,
-    play: TextFragment,
-    settings: TextFragment,
+    title: String,
+    font: Option<BitmapFont>,
+    selection_hotspot: glam::Vec2,
     world: World,
 }
 
 impl TitleScreenSystem {
     /// Creates a new title screen system.
     pub fn new(game_title: &str) -> Self {
-        let title = TextFragment::new(game_title)
-            .color(Color::WHITE)
-            .scale(PxScale::from(72.0));
-        let play = TextFragment::new("Play")
-            .color(Color::WHITE)
-            .scale(PxScale::from(24.0));
-        let settings = TextFragment::new("Settings")
-            .color(Color::WHITE)
-            .scale(PxScale::from(24.0));
-
         let world = World::default();
         Self {
-            title,
-            play,
-            settings,
+            title: game_title.to_string(),
+            font: None,
+            selection_hotspot: glam::Vec2::ZERO,
             world,
         }
     }
 
-    /// Sets up the title screen system's initial state.
-    pub fn setup(&mut self, _context: &mut Context) -> GameResult {
+    /// Sets up the title screen system's initial state, laying out the
+    /// title and the menu, which never change.
+    pub fn setup(&mut self, context: &mut Context) -> GameResult {
+        let mut font = BitmapFont::load(context, FONT_PATH)?;
+        let screen = graphics::screen_coordinates(context);
+        let center = glam::vec2(screen.w / 2.0, screen.h / 2.0).round();
+
+        let title = font.layout(
+            &self.title,
+            &TextStyle {
+                align: Align::Center,
+                scale: TITLE_SCALE,
+                ..TextStyle::default()
+            },
+        );
+        let menu = font.layout(
+            &MENU_OPTIONS.join("\n"),
+            &TextStyle {
+                align: Align::Center,
+                ..TextStyle::default()
+            },
+        );
+        font.queue(&title, center - glam::vec2(0.0, title.size().y))?;
+        font.queue(&menu, center + glam::vec2(0.0, font.line_height()))?;
+
+        self.selection_hotspot = center
+            + glam::vec2(
+                -(menu.size().x / 2.0).round() - 10.0,
+                font.line_height() + 1.0,
+            );
         let _ = self.world.push((
             objects::Marker {
-                num_options: 2,
-                draw_step: 25.0,
+                num_options: MENU_OPTIONS.len() as u64,
+                draw_step: font.line_height(),
             },
             Position::default(),
             Tag::default(),
         ));
+        self.font = Some(font);
 
         Ok(())
     }
 
     /// Updates the title screen system.
-    pub fn update(&mut self, navigation: &mut Navigation, input: &Input) -> GameResult {
-        objects::Marker::update(&mut self.world, navigation, input)
+    pub fn update(&mut self, transitions: &mut Transitions, input: &Input) -> GameResult {
+        objects::Marker::update(&mut self.world, transitions, input)
     }
 
     /// Draws the title screen system.
     pub fn draw(&self, context: &mut Context) -> GameResult {
-        let title = Text::new(self.title.clone());
-        let play = Text::new(self.play.clone());
-        let settings = Text::new(self.settings.clone());
-
-        // Widths and heights for screen and the three texts
-        let (screen_width, screen_height) = {
-            let rect = graphics::screen_coordinates(context);
-            (rect.w, rect.h)
-        };
-
-        let (title_width, title_height) = {
-            let rect = title.dimensions(context);
-            (rect.w, rect.h)
-        };
-
-        let (play_width, play_height) = {
-            let rect = play.dimensions(context);
-            (rect.w, rect.h)
-        };
-
-        let (settings_width, settings_height) = {
-            let rect = settings.dimensions(context);
-            (rect.w, rect.h)
-        };
-
-        let title_destination = [
-            (screen_width / 2.0) - (title_width / 2.0),
-            (screen_height / 2.0) - title_height,
-        ];
-
-        let play_destination = [
-            (screen_width / 2.0) - (play_width / 2.0),
-            (screen_height / 2.0) - (play_height / 2.0) + title_height,
-        ];
-
-        let settings_destination = [
-            (screen_width / 2.0) - (settings_width / 2.0),
-            (screen_height / 2.0) - (settings_height / 2.0) + title_height + play_height,
-        ];
-
-        let selection_hotspot = glam::vec2(
-            (screen_width / 2.0) - (settings_width / 2.0) - 30.0,
-            (screen_height / 2.0) - (play_height / 2.0) + title_height + 5.0,
-        );
-
-        graphics::queue_text(context, &title, title_destination, None);
-        graphics::queue_text(context, &play, play_destination, None);
-        graphics::queue_text(context, &settings, settings_destination, None);
-
-        objects::Marker::draw(&self.world, context, selection_hotspot)?;
-
-        Ok(())
+        if let Some(font) = &self.font {
+            font.draw(context)?;
+        }
+        objects::Marker::draw(&self.world, context, self.selection_hotspot)
     }
 }
@@ -0,0 +1,171 @@
+use super::Navigation;
+use ggez::graphics::{self, BlendMode, Color, DrawMode, DrawParam, Drawable, MeshBuilder, Rect};
+use ggez::{Context, GameResult};
+
+/// Amount of brightness levels of each color channel during palette
+/// fades, as on the Mega Drive.
+const PALETTE_FADE_STEPS: f32 = 7.0;
+
+/// Enumeration for describing the directions wipes move towards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WipeDirection {
+    Left,
+    Right,
+}
+
+/// Enumeration for describing how the screen is covered and uncovered
+/// during a transition.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransitionEffect {
+    /// Blends the screen into a solid color.
+    Fade(Color),
+    /// Steps every color channel down to black, so that bright colors
+    /// fade out last, like the classic palette fade.
+    PaletteFadeBlack,
+    /// Sweeps a black curtain over the screen, which then leaves
+    /// through the opposite side.
+    Wipe(WipeDirection),
+}
+
+/// Phase of a transition, either covering the screen being left or
+/// uncovering the screen being entered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TransitionPhase {
+    Out,
+    In,
+}
+
+/// A screen transition in progress.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Transition {
+    target: Navigation,
+    effect: TransitionEffect,
+    frames: u32,
+    timer: u32,
+    phase: TransitionPhase,
+}
+
+impl Transition {
+    /// Gets how much of the screen is covered by the effect, from 0
+    /// to 1.
+    fn coverage(&self) -> f32 {
+        let progress = self.timer as f32 / self.frames as f32;
+        match self.phase {
+            TransitionPhase::Out => progress,
+            TransitionPhase::In => 1.0 - progress,
+        }
+    }
+}
+
+/// Handles changes between screens, so that they do not happen
+/// instantly.
+///
+/// Screens request a change along with an effect. The effect covers
+/// the current screen, which is frozen meanwhile, then the screen is
+/// switched and the effect uncovers the new screen, which runs with no
+/// input until the transition ends.
+#[derive(Default)]
+pub struct Transitions {
+    current: Option<Transition>,
+}
+
+impl Transitions {
+    /// Requests a change to another screen, with an effect lasting a
+    /// certain amount of frames on each phase. Requests made while
+    /// another transition is in progress are ignored.
+    pub fn request(&mut self, target: Navigation, effect: TransitionEffect, frames: u32) {
+        if self.current.is_some() {
+            return;
+        }
+        self.current = Some(Transition {
+            target,
+            effect,
+            frames: frames.max(1),
+            timer: 0,
+            phase: TransitionPhase::Out,
+        });
+    }
+
+    /// Whether the current screen is being covered, and so should not
+    /// be updated.
+    pub fn freezes(&self) -> bool {
+        matches!(self.current, Some(transition) if transition.phase == TransitionPhase::Out)
+    }
+
+    /// Whether input should be ignored by screens.
+    pub fn blocks_input(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Advances the current transition by a single frame, switching
+    /// screens once the current one is fully covered.
+    pub fn update(&mut self, navigation: &mut Navigation) {
+        let transition = match self.current.as_mut() {
+            Some(transition) => transition,
+            None => return,
+        };
+        transition.timer += 1;
+        if transition.timer < transition.frames {
+            return;
+        }
+        match transition.phase {
+            TransitionPhase::Out => {
+                *navigation = transition.target;
+                transition.phase = TransitionPhase::In;
+                transition.timer = 0;
+            }
+            TransitionPhase::In => self.current = None,
+        }
+    }
+
+    /// Draws the current transition over the whole screen.
+    pub fn draw(&self, context: &mut Context) -> GameResult {
+        let transition = match &self.current {
+            Some(transition) => transition,
+            None => return Ok(()),
+        };
+        let coverage = transition.coverage();
+        let screen = graphics::screen_coordinates(context);
+        let mut builder = MeshBuilder::new();
+        let blend_mode = match transition.effect {
+            TransitionEffect::Fade(color) => {
+                let color = Color {
+                    a: color.a * coverage,
+                    ..color
+                };
+                builder.rectangle(DrawMode::fill(), screen, color)?;
+                BlendMode::Alpha
+            }
+            TransitionEffect::PaletteFadeBlack => {
+                let level = (coverage * PALETTE_FADE_STEPS).round() / PALETTE_FADE_STEPS;
+                builder.rectangle(
+                    DrawMode::fill(),
+                    screen,
+                    Color::new(level, level, level, 1.0),
+                )?;
+                BlendMode::Subtract
+            }
+            TransitionEffect::Wipe(direction) => {
+                // The curtain enters from one side and leaves through
+                // the opposite one
+                let offset = match transition.phase {
+                    TransitionPhase::Out => coverage - 1.0,
+                    TransitionPhase::In => 1.0 - coverage,
+                };
+                let rect = match direction {
+                    WipeDirection::Right => Rect::new(screen.w * offset, 0.0, screen.w, screen.h),
+                    WipeDirection::Left => Rect::new(-screen.w * offset, 0.0, screen.w, screen.h),
+                };
+                builder.rectangle(
+                    DrawMode::fill(),
+                    Rect::new(screen.x + rect.x, screen.y + rect.y, rect.w, rect.h),
+                    Color::BLACK,
+                )?;
+                BlendMode::Alpha
+            }
+        };
+        let mut mesh = builder.build(context)?;
+        mesh.set_blend_mode(Some(blend_mode));
+        mesh.draw(context, DrawParam::default())
+    }
+}